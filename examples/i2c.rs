@@ -85,7 +85,7 @@ async fn main(_spawner: Spawner) {
 
     let mut disp: GraphicsMode<_, _> = raw_disp.into();
 
-    disp.init().await.unwrap();
+    disp.init(&mut delay).await.unwrap();
     disp.clear();
     disp.flush().await.unwrap();
 
@@ -57,7 +57,7 @@ async fn main(_spawner: Spawner) {
     // Init cs, dc and reset I/O pins
     let cs = Output::new(p.PA2, Level::High, Speed::High);
     let dc = Output::new(p.PA3, Level::High, Speed::High);
-    let mut reset = Output::new(p.PA4, Level::High, Speed::High);
+    let reset = Output::new(p.PA4, Level::High, Speed::High);
 
     // Initialise the SPI peripheral
     let spi = embassy_stm32::spi::Spi::new(
@@ -74,12 +74,12 @@ async fn main(_spawner: Spawner) {
 
     let raw_disp = Builder::new(oled_async::displays::sh1107::Sh1107_128_128 {})
         .with_rotation(crate::DisplayRotation::Rotate180)
+        .with_reset_pin(reset)
         .connect(di);
 
-    let mut disp: GraphicsMode<_, _> = raw_disp.into();
+    let mut disp: GraphicsMode<_, _, [u8; 128 * 128 / 8], _> = raw_disp.into();
 
-    disp.reset(&mut reset, &mut delay).unwrap();
-    disp.init().await.unwrap();
+    disp.reset_and_init(&mut delay).await.unwrap();
     disp.clear();
     disp.flush().await.unwrap();
 
@@ -40,20 +40,22 @@ use {defmt_rtt as _, panic_probe as _};
 
 #[embassy_executor::main]
 async fn main(_spawner: Spawner) {
-    let (di, mut reset, mut delay) = bsp::board::get_board();
+    let (di, reset, mut delay) = bsp::board::get_board();
 
     type Display = oled_async::displays::sh1107::Sh1107_128_128;
+    //type Display = oled_async::displays::sh1106::Sh1106_128_64;
     //type Display = oled_async::displays::sh1108::Sh1108_64_160;
+    //type Display = oled_async::displays::ssd1306::Ssd1306_128_64;
     //type Display = oled_async::displays::ssd1309::Ssd1309_128_64;
 
     let raw_disp = Builder::new(Display {})
         .with_rotation(crate::DisplayRotation::Rotate180)
+        .with_reset_pin(reset)
         .connect(di);
 
-    let mut display: GraphicsMode<_, _, { 128 * 128 / 8 }> = raw_disp.into();
+    let mut display: GraphicsMode<_, _, [u8; 128 * 128 / 8], _> = raw_disp.into();
 
-    display.reset(&mut reset, &mut delay).unwrap();
-    display.init().await.unwrap();
+    display.reset_and_init(&mut delay).await.unwrap();
     display.clear();
     display.flush().await.unwrap();
 
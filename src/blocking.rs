@@ -0,0 +1,145 @@
+//! Blocking front end for applications without an async executor
+//!
+//! The driver is written against the async traits only, so that every display variant, init
+//! table and mode exists once. This module adapts a blocking
+//! [`WriteOnlyDataCommand`] and [`embedded_hal::delay::DelayNs`] to them. With a
+//! [`BlockingInterface`] every driver future finishes without ever having to wait, and
+//! [`BlockOn::block_on`] runs it to completion in place:
+//!
+//! ```rust,ignore
+//! use oled_async::blocking::{BlockOn, BlockingDelay, BlockingInterface};
+//!
+//! let mut display: GraphicsMode<_, _, [u8; Sh1107_128_128::BUFFER_SIZE]> =
+//!     Builder::new(Sh1107_128_128 {})
+//!         .connect(BlockingInterface::new(display_interface))
+//!         .into();
+//! let mut delay = BlockingDelay::new(delay);
+//!
+//! display.init(&mut delay).block_on().unwrap();
+//! Text::new("Hello", Point::new(0, 8), style).draw(&mut display).unwrap();
+//! display.flush().block_on().unwrap();
+//! ```
+
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use display_interface::{
+    AsyncWriteOnlyDataCommand, DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+
+/// Async display interface that forwards to a blocking [`WriteOnlyDataCommand`]
+#[derive(Debug)]
+pub struct BlockingInterface<DI> {
+    iface: DI,
+}
+
+impl<DI: WriteOnlyDataCommand> BlockingInterface<DI> {
+    /// Wrap a blocking display interface
+    pub fn new(iface: DI) -> Self {
+        Self { iface }
+    }
+
+    /// Release the blocking display interface
+    pub fn release(self) -> DI {
+        self.iface
+    }
+}
+
+impl<DI: WriteOnlyDataCommand> AsyncWriteOnlyDataCommand for BlockingInterface<DI> {
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.iface.send_commands(cmd)
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.iface.send_data(buf)
+    }
+}
+
+/// Async delay that forwards to a blocking [`embedded_hal::delay::DelayNs`]
+#[derive(Debug)]
+pub struct BlockingDelay<D> {
+    delay: D,
+}
+
+impl<D: hal::delay::DelayNs> BlockingDelay<D> {
+    /// Wrap a blocking delay
+    pub fn new(delay: D) -> Self {
+        Self { delay }
+    }
+
+    /// Release the blocking delay
+    pub fn release(self) -> D {
+        self.delay
+    }
+}
+
+impl<D: hal::delay::DelayNs> embedded_hal_async::delay::DelayNs for BlockingDelay<D> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns)
+    }
+}
+
+/// Run a future to completion on the current thread. The driver only waits on the interface and
+/// the delay, so with [`BlockingInterface`] and [`BlockingDelay`] the future completes on its
+/// first poll. Any other future is polled in a busy loop until it completes.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut context = Context::from_waker(Waker::noop());
+    let mut future = pin!(future);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// Run any driver call to completion with [`block_on`], e.g. `display.flush().block_on()`
+pub trait BlockOn: Future + Sized {
+    /// Block until the future completes and return its output
+    fn block_on(self) -> Self::Output {
+        block_on(self)
+    }
+}
+
+impl<F: Future> BlockOn for F {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        displays::sh1106::Sh1106_128_64,
+        mode::GraphicsMode,
+        test_helpers::{commands, data, init_transfers, MockInterface},
+        Builder,
+    };
+
+    #[test]
+    fn init_and_flush() {
+        let iface = MockInterface::new();
+        let mut display: GraphicsMode<_, _> = Builder::new(Sh1106_128_64 {})
+            .connect(BlockingInterface::new(iface.clone()))
+            .into();
+
+        display
+            .init(&mut BlockingDelay::new(iface.clone()))
+            .block_on()
+            .unwrap();
+        assert_eq!(iface.take(), init_transfers(Sh1106_128_64 {}));
+
+        display.clear();
+        display.set_pixel(0, 0, 1);
+        display.flush().block_on().unwrap();
+
+        let transfers = iface.take();
+        assert_eq!(
+            transfers[..2],
+            [
+                commands([&[0xB0], &[0x02], &[0x10]]),
+                data(&[&[1][..], &[0; 127]].concat()),
+            ]
+        );
+    }
+}
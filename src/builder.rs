@@ -10,7 +10,7 @@
 //!
 //! Connect 180 deg rotation to a 128x128 SH1107 based display:
 //!
-//! ```rust,no_run
+//! ```rust,ignore
 //! use oled_async::{mode::GraphicsMode, Builder};
 //! let spi = /* Create an SPI 'device' that implements embedded_hal::SpiDevice  using a HAL of your choice */
 //! let di = /*  Use spi to create an interface that implements display_interface::AsyncWriteOnlyDataCommand using a bus that matches your hardware such as display_interface_spi::SPIInterface */
@@ -30,7 +30,7 @@
 //! by default. You need to coerce them into a mode by specifying a type on assignment. For
 //! example, to use [`GraphicsMode` mode](../mode/graphics/struct.GraphicsMode.html):
 //!
-//! ```rust,no_run
+//! ```rust,ignore
 //! use oled_async::{mode::GraphicsMode, Builder};
 //! let mut display: GraphicsMode<_, _> = raw_display.into();
 //! ```
@@ -39,6 +39,8 @@ use display_interface::AsyncWriteOnlyDataCommand;
 use hal::digital::OutputPin;
 
 use crate::{
+    command::VcomhLevel,
+    config::DisplayConfig,
     displayrotation::DisplayRotation,
     mode::{displaymode::DisplayMode, raw::RawMode},
     properties::DisplayProperties,
@@ -48,41 +50,102 @@ use crate::{
 ///
 /// See the [module level documentation](crate::builder) for more details.
 #[derive(Clone, Copy)]
-pub struct Builder<DV> {
+pub struct Builder<DV, RST = NoOutputPin> {
     variant: DV,
     rotation: DisplayRotation,
+    config: DisplayConfig,
+    reset_pin: RST,
 }
 
 impl<DV> Builder<DV> {
-    /// Create new builder with a default size of 128 x 64 pixels and no rotation.
+    /// Create new builder with a default size of 128 x 64 pixels, no rotation and no reset pin.
     pub fn new(variant: DV) -> Builder<DV> {
         Builder::<DV> {
             variant,
             rotation: DisplayRotation::Rotate0,
+            config: DisplayConfig::default(),
+            reset_pin: NoOutputPin,
         }
     }
 }
 
-impl<DV> Builder<DV> {
+impl<DV, RST> Builder<DV, RST> {
     /// Set the rotation of the display to one of four values. Defaults to no rotation.
     pub fn with_rotation(self, rotation: DisplayRotation) -> Self {
         Self { rotation, ..self }
     }
 
+    /// Replace all settings applied when initialising the display
+    pub fn with_config(self, config: DisplayConfig) -> Self {
+        Self { config, ..self }
+    }
+
+    /// Set the contrast sent when initialising the display
+    pub fn with_contrast(mut self, contrast: u8) -> Self {
+        self.config.contrast = Some(contrast);
+        self
+    }
+
+    /// Set the oscillator frequency and display clock divide ratio, both 0-15
+    pub fn with_clock(mut self, fosc: u8, div: u8) -> Self {
+        self.config.clock = Some((fosc, div));
+        self
+    }
+
+    /// Set the pre-charge phase 1 and phase 2 periods in display clocks, both 1-15
+    pub fn with_precharge(mut self, phase1: u8, phase2: u8) -> Self {
+        self.config.precharge = Some((phase1, phase2));
+        self
+    }
+
+    /// Set the VCOMH deselect level
+    pub fn with_vcomh(mut self, level: VcomhLevel) -> Self {
+        self.config.vcomh = Some(level);
+        self
+    }
+
+    /// Select whether VCC is supplied externally, in which case the internal charge pump or DC-DC
+    /// converter is left off. Defaults to an internally generated VCC.
+    pub fn with_external_vcc(mut self, external_vcc: bool) -> Self {
+        self.config.external_vcc = external_vcc;
+        self
+    }
+
+    /// Let the driver own the reset pin of the display, so that modes can `reset` it. Without a
+    /// reset pin `reset` only waits.
+    pub fn with_reset_pin<R: OutputPin>(self, reset_pin: R) -> Builder<DV, R> {
+        Builder {
+            variant: self.variant,
+            rotation: self.rotation,
+            config: self.config,
+            reset_pin,
+        }
+    }
+
     /// Finish the builder and use the given interface to communicate with the display.
-    pub fn connect<DI>(self, interface: DI) -> DisplayMode<RawMode<DV, DI>>
+    pub fn connect<DI>(self, interface: DI) -> DisplayMode<RawMode<DV, DI, RST>>
     where
         DI: AsyncWriteOnlyDataCommand,
         DV: crate::display::DisplayVariant,
+        RST: OutputPin,
     {
-        let properties = DisplayProperties::new(self.variant, interface, self.rotation);
-        DisplayMode::<RawMode<DV, DI>>::new(properties)
+        let properties =
+            DisplayProperties::new(self.variant, interface, self.rotation, self.config)
+                .with_reset_pin(self.reset_pin);
+        DisplayMode::<RawMode<DV, DI, RST>>::new(properties)
     }
 }
 
 /// Marker type for no reset pin.
-#[derive(Clone, Copy)]
-pub enum NoOutputPin {}
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoOutputPin;
+
+impl NoOutputPin {
+    /// Create a new pin that ignores all operations
+    pub fn new() -> Self {
+        NoOutputPin
+    }
+}
 
 impl OutputPin for NoOutputPin {
     fn set_low(&mut self) -> Result<(), Self::Error> {
@@ -100,21 +163,45 @@ impl hal::digital::ErrorType for NoOutputPin {
 
 #[cfg(test)]
 mod tests {
-    use super::NoOutputPin;
-    use embedded_hal::digital::v2::OutputPin;
-
-    enum SomeError {}
+    use super::{Builder, NoOutputPin};
+    use crate::{command::VcomhLevel, config::DisplayConfig, displays::sh1106::Sh1106_128_64};
+    use embedded_hal::digital::OutputPin;
 
-    struct SomeDriver<P: OutputPin<Error = SomeError>> {
-        #[allow(dead_code)]
+    struct SomeDriver<P: OutputPin> {
         p: P,
     }
 
     #[test]
     fn test_output_pin() {
         let p = NoOutputPin::new();
-        let _d = SomeDriver { p };
+        let mut d = SomeDriver { p };
+
+        assert!(d.p.set_low().is_ok());
+        assert!(d.p.set_high().is_ok());
+    }
+
+    #[test]
+    fn config() {
+        let builder = Builder::new(Sh1106_128_64 {})
+            .with_contrast(0x20)
+            .with_clock(0xF, 0x0)
+            .with_precharge(0x2, 0x2)
+            .with_vcomh(VcomhLevel::V065)
+            .with_external_vcc(true);
 
-        assert!(true);
+        assert_eq!(
+            builder.config,
+            DisplayConfig {
+                contrast: Some(0x20),
+                clock: Some((0xF, 0x0)),
+                precharge: Some((0x2, 0x2)),
+                vcomh: Some(VcomhLevel::V065),
+                external_vcc: true,
+            }
+        );
+        assert_eq!(
+            builder.with_config(DisplayConfig::default()).config,
+            DisplayConfig::default()
+        );
     }
 }
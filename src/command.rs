@@ -1,10 +1,10 @@
-use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+//! oled_async Commands
 
-/// oled_async Commands
+use crate::Error;
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat};
 
 /// Commands
-#[derive(Debug)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
 pub enum Command {
     /// Set the addressing mode.
     /// `false` is page addressing mode.
@@ -56,18 +56,53 @@ pub enum Command {
     VcomhDeselect(VcomhLevel),
     /// NOOP
     Noop,
-    /// Enable charge pump
+    /// Enable charge pump. On the SH1106 this is the DC-DC control mode register.
     ChargePump(bool),
+    /// Set the memory addressing mode (SSD130x).
+    /// Unlike `AddressMode` the mode is sent as a separate byte.
+    MemoryAddressingMode(AddressingMode),
+    /// Enable charge pump (SSD1306)
+    /// Unlike `ChargePump` this uses the 0x8D opcode.
+    SsdChargePump(bool),
+    /// Set the column start and end address used in horizontal and vertical addressing mode
+    /// (SSD130x)
+    ColumnRange(u8, u8),
+    /// Set the page start and end address used in horizontal and vertical addressing mode
+    /// (SSD130x)
+    PageRange(u8, u8),
+    /// Setup continuous horizontal scroll.
+    /// Values are direction, start page, end page and the interval between scroll steps
+    HorizontalScroll(ScrollDirection, u8, u8, NFrames),
+    /// Setup continuous vertical and horizontal scroll.
+    /// Values are direction, start page, end page, the interval between scroll steps and the
+    /// vertical offset in rows per step
+    VerticalAndHorizontalScroll(ScrollDirection, u8, u8, NFrames, u8),
+    /// Scroll the content by one column (SSD1309).
+    /// Values are direction, start page, end page, start column and end column
+    OneColumnScroll(ScrollDirection, u8, u8, u8, u8),
+    /// Activate or deactivate the scroll set up by one of the scroll commands
+    EnableScroll(bool),
+    /// Set the vertical scroll area.
+    /// First value is the number of fixed rows at the top, second the number of scrolling rows
+    VerticalScrollArea(u8, u8),
 }
 
 impl Command {
     /// Send command to oled_async
-    pub async fn send<DI>(self, iface: &mut DI) -> Result<(), DisplayError>
+    pub async fn send<DI>(self, iface: &mut DI) -> Result<(), Error>
     where
         DI: AsyncWriteOnlyDataCommand,
     {
-        // Transform command into a fixed size array of 7 u8 and the real length for sending
-        let (data, len) = match self {
+        let (data, len) = self.encode();
+        // Send command over the interface
+        iface.send_commands(DataFormat::U8(&data[0..len])).await?;
+
+        Ok(())
+    }
+
+    /// Transform the command into a fixed size array of 7 u8 and the real length for sending
+    pub fn encode(self) -> ([u8; 7], usize) {
+        match self {
             Command::AddressMode(mode) => ([0x20 | (mode as u8), 0, 0, 0, 0, 0, 0], 1),
             Command::Contrast(val) => ([0x81, val, 0, 0, 0, 0, 0], 2),
             Command::AllOn(on) => ([0xA4 | (on as u8), 0, 0, 0, 0, 0, 0], 1),
@@ -94,9 +129,141 @@ impl Command {
             Command::VcomhDeselect(level) => ([0xDB, (level as u8) << 4, 0, 0, 0, 0, 0], 2),
             Command::Noop => ([0xE3, 0, 0, 0, 0, 0, 0], 1),
             Command::ChargePump(en) => ([0xAD, 0x8A | (en as u8), 0, 0, 0, 0, 0], 2),
-        };
-        // Send command over the interface
-        iface.send_commands(DataFormat::U8(&data[0..len])).await
+            Command::MemoryAddressingMode(mode) => ([0x20, mode as u8, 0, 0, 0, 0, 0], 2),
+            Command::SsdChargePump(en) => ([0x8D, 0x10 | ((en as u8) << 2), 0, 0, 0, 0, 0], 2),
+            Command::ColumnRange(start, end) => ([0x21, start, end, 0, 0, 0, 0], 3),
+            Command::PageRange(start, end) => ([0x22, start, end, 0, 0, 0, 0], 3),
+            Command::HorizontalScroll(dir, start, end, interval) => (
+                [
+                    0x26 | (dir as u8),
+                    0,
+                    0x7 & start,
+                    interval as u8,
+                    0x7 & end,
+                    0,
+                    0xFF,
+                ],
+                7,
+            ),
+            Command::VerticalAndHorizontalScroll(dir, start, end, interval, offset) => (
+                [
+                    0x29 + (dir as u8),
+                    0,
+                    0x7 & start,
+                    interval as u8,
+                    0x7 & end,
+                    0x3F & offset,
+                    0,
+                ],
+                6,
+            ),
+            Command::OneColumnScroll(dir, start, end, start_col, end_col) => (
+                [
+                    0x2C | (dir as u8),
+                    0,
+                    0x7 & start,
+                    0x1,
+                    0x7 & end,
+                    0x7F & start_col,
+                    0x7F & end_col,
+                ],
+                7,
+            ),
+            Command::EnableScroll(on) => ([0x2E | (on as u8), 0, 0, 0, 0, 0, 0], 1),
+            Command::VerticalScrollArea(fixed, scroll) => ([0xA3, fixed, scroll, 0, 0, 0, 0], 3),
+        }
+    }
+}
+
+/// Commands encoded back to back and sent in a single `send_commands` call. Over I2C this saves
+/// the start condition, address and control byte of every command but the first.
+///
+/// Holds up to `N` bytes of commands. A command is at most 7 bytes long.
+#[derive(Debug, Clone, Copy)]
+pub struct CommandBatch<const N: usize = 32> {
+    data: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> CommandBatch<N> {
+    /// Create an empty batch
+    pub const fn new() -> Self {
+        Self {
+            data: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Append a command, or return [`Error::BufferTooSmall`] if it does not fit
+    pub fn push(&mut self, command: Command) -> Result<&mut Self, Error> {
+        let (data, len) = command.encode();
+        let end = self.len + len;
+        if end > N {
+            return Err(Error::BufferTooSmall);
+        }
+
+        self.data[self.len..end].copy_from_slice(&data[..len]);
+        self.len = end;
+
+        Ok(self)
+    }
+
+    /// Append raw command bytes, e.g. a controller specific opcode and its arguments, or return
+    /// [`Error::BufferTooSmall`] if they do not fit
+    pub fn push_raw(&mut self, bytes: &[u8]) -> Result<&mut Self, Error> {
+        let end = self.len + bytes.len();
+        if end > N {
+            return Err(Error::BufferTooSmall);
+        }
+
+        self.data[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+
+        Ok(self)
+    }
+
+    /// Append a command, sending the batch first if the command does not fit
+    pub async fn queue<DI>(&mut self, iface: &mut DI, command: Command) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        if self.push(command).is_err() {
+            self.send(iface).await?;
+            self.push(command)?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether no commands are waiting to be sent
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The encoded commands
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    /// Send all commands in one transfer and empty the batch. Does nothing if it is empty.
+    pub async fn send<DI>(&mut self, iface: &mut DI) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        iface.send_commands(DataFormat::U8(self.as_bytes())).await?;
+        self.len = 0;
+
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for CommandBatch<N> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -122,9 +289,17 @@ pub enum NFrames {
     F256 = 0b011,
 }
 
-/// Vcomh Deselect level
+/// Horizontal scroll direction
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
+pub enum ScrollDirection {
+    /// Content moves to the right
+    Right = 0,
+    /// Content moves to the left
+    Left = 1,
+}
+
+/// Vcomh Deselect level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VcomhLevel {
     /// 0.65 * Vcc
     V065 = 0b001,
@@ -135,3 +310,242 @@ pub enum VcomhLevel {
     /// Auto
     Auto = 0b100,
 }
+
+/// Memory addressing mode (SSD130x)
+#[derive(Debug, Clone, Copy)]
+#[allow(dead_code)]
+pub enum AddressingMode {
+    /// Column address wraps to the next page at the end of each page
+    Horizontal = 0b00,
+    /// Page address wraps to the next column at the end of each column
+    Vertical = 0b01,
+    /// Column address wraps within the current page
+    Page = 0b10,
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::test_helpers::{block_on, command, commands, MockInterface, Transfer};
+
+    fn encode(cmd: Command) -> Vec<Transfer> {
+        let mut iface = MockInterface::new();
+        block_on(cmd.send(&mut iface)).unwrap();
+        iface.take()
+    }
+
+    #[test]
+    fn single_byte_commands() {
+        assert_eq!(encode(Command::AddressMode(false)), [command(&[0x20])]);
+        assert_eq!(encode(Command::AddressMode(true)), [command(&[0x21])]);
+        assert_eq!(encode(Command::AllOn(false)), [command(&[0xA4])]);
+        assert_eq!(encode(Command::AllOn(true)), [command(&[0xA5])]);
+        assert_eq!(encode(Command::Invert(false)), [command(&[0xA6])]);
+        assert_eq!(encode(Command::Invert(true)), [command(&[0xA7])]);
+        assert_eq!(encode(Command::DisplayOn(false)), [command(&[0xAE])]);
+        assert_eq!(encode(Command::DisplayOn(true)), [command(&[0xAF])]);
+        assert_eq!(encode(Command::ColumnAddressLow(0x12)), [command(&[0x02])]);
+        assert_eq!(encode(Command::ColumnAddressHigh(0x7)), [command(&[0x17])]);
+        assert_eq!(encode(Command::PageAddress(3)), [command(&[0xB3])]);
+        assert_eq!(encode(Command::StartLine(0x48)), [command(&[0x48])]);
+        assert_eq!(encode(Command::SegmentRemap(false)), [command(&[0xA0])]);
+        assert_eq!(encode(Command::SegmentRemap(true)), [command(&[0xA1])]);
+        assert_eq!(encode(Command::ReverseComDir(false)), [command(&[0xC0])]);
+        assert_eq!(encode(Command::ReverseComDir(true)), [command(&[0xC8])]);
+        assert_eq!(encode(Command::Noop), [command(&[0xE3])]);
+        assert_eq!(encode(Command::EnableScroll(false)), [command(&[0x2E])]);
+        assert_eq!(encode(Command::EnableScroll(true)), [command(&[0x2F])]);
+    }
+
+    #[test]
+    fn commands_with_arguments() {
+        assert_eq!(encode(Command::Contrast(0x7F)), [command(&[0x81, 0x7F])]);
+        assert_eq!(
+            encode(Command::DisplayResolution(2)),
+            [command(&[0xA9, 0x02])]
+        );
+        assert_eq!(
+            encode(Command::LargePageAddress(19)),
+            [command(&[0xB0, 19])]
+        );
+        assert_eq!(encode(Command::Multiplex(63)), [command(&[0xA8, 63])]);
+        assert_eq!(encode(Command::DisplayOffset(32)), [command(&[0xD3, 32])]);
+        assert_eq!(
+            encode(Command::ComPinConfig(false)),
+            [command(&[0xDA, 0x02])]
+        );
+        assert_eq!(
+            encode(Command::ComPinConfig(true)),
+            [command(&[0xDA, 0x12])]
+        );
+        assert_eq!(
+            encode(Command::DisplayClockDiv(0x8, 0x1)),
+            [command(&[0xD5, 0x81])]
+        );
+        assert_eq!(
+            encode(Command::PreChargePeriod(0x1, 0xF)),
+            [command(&[0xD9, 0xF1])]
+        );
+        assert_eq!(
+            encode(Command::VcomhDeselect(VcomhLevel::V065)),
+            [command(&[0xDB, 0x10])]
+        );
+        assert_eq!(
+            encode(Command::VcomhDeselect(VcomhLevel::V077)),
+            [command(&[0xDB, 0x20])]
+        );
+        assert_eq!(
+            encode(Command::VcomhDeselect(VcomhLevel::V083)),
+            [command(&[0xDB, 0x30])]
+        );
+        assert_eq!(
+            encode(Command::VcomhDeselect(VcomhLevel::Auto)),
+            [command(&[0xDB, 0x40])]
+        );
+        assert_eq!(encode(Command::ChargePump(false)), [command(&[0xAD, 0x8A])]);
+        assert_eq!(encode(Command::ChargePump(true)), [command(&[0xAD, 0x8B])]);
+    }
+
+    #[test]
+    fn ssd130x_commands() {
+        assert_eq!(
+            encode(Command::MemoryAddressingMode(AddressingMode::Horizontal)),
+            [command(&[0x20, 0x00])]
+        );
+        assert_eq!(
+            encode(Command::MemoryAddressingMode(AddressingMode::Vertical)),
+            [command(&[0x20, 0x01])]
+        );
+        assert_eq!(
+            encode(Command::MemoryAddressingMode(AddressingMode::Page)),
+            [command(&[0x20, 0x02])]
+        );
+        assert_eq!(
+            encode(Command::SsdChargePump(false)),
+            [command(&[0x8D, 0x10])]
+        );
+        assert_eq!(
+            encode(Command::SsdChargePump(true)),
+            [command(&[0x8D, 0x14])]
+        );
+        assert_eq!(
+            encode(Command::ColumnRange(28, 99)),
+            [command(&[0x21, 28, 99])]
+        );
+        assert_eq!(encode(Command::PageRange(0, 7)), [command(&[0x22, 0, 7])]);
+    }
+
+    #[test]
+    fn scroll_commands() {
+        assert_eq!(
+            encode(Command::HorizontalScroll(
+                ScrollDirection::Right,
+                0,
+                7,
+                NFrames::F5
+            )),
+            [command(&[0x26, 0x00, 0x00, 0x00, 0x07, 0x00, 0xFF])]
+        );
+        assert_eq!(
+            encode(Command::HorizontalScroll(
+                ScrollDirection::Left,
+                2,
+                3,
+                NFrames::F2
+            )),
+            [command(&[0x27, 0x00, 0x02, 0x07, 0x03, 0x00, 0xFF])]
+        );
+        assert_eq!(
+            encode(Command::VerticalAndHorizontalScroll(
+                ScrollDirection::Right,
+                0,
+                7,
+                NFrames::F64,
+                1
+            )),
+            [command(&[0x29, 0x00, 0x00, 0x01, 0x07, 0x01])]
+        );
+        assert_eq!(
+            encode(Command::VerticalAndHorizontalScroll(
+                ScrollDirection::Left,
+                1,
+                6,
+                NFrames::F256,
+                63
+            )),
+            [command(&[0x2A, 0x00, 0x01, 0x03, 0x06, 0x3F])]
+        );
+        assert_eq!(
+            encode(Command::OneColumnScroll(
+                ScrollDirection::Right,
+                0,
+                7,
+                0,
+                127
+            )),
+            [command(&[0x2C, 0x00, 0x00, 0x01, 0x07, 0x00, 0x7F])]
+        );
+        assert_eq!(
+            encode(Command::OneColumnScroll(
+                ScrollDirection::Left,
+                0,
+                7,
+                0,
+                127
+            )),
+            [command(&[0x2D, 0x00, 0x00, 0x01, 0x07, 0x00, 0x7F])]
+        );
+        assert_eq!(
+            encode(Command::VerticalScrollArea(8, 56)),
+            [command(&[0xA3, 8, 56])]
+        );
+    }
+
+    #[test]
+    fn batch() {
+        let mut iface = MockInterface::new();
+        let mut batch = CommandBatch::<5>::new();
+
+        block_on(batch.send(&mut iface)).unwrap();
+        assert_eq!(iface.take(), []);
+
+        batch
+            .push(Command::DisplayOn(false))
+            .unwrap()
+            .push(Command::Contrast(0x10))
+            .unwrap();
+        assert!(matches!(
+            batch.push(Command::ColumnRange(0, 127)),
+            Err(Error::BufferTooSmall)
+        ));
+
+        // Sends the full batch before queueing the command that does not fit
+        block_on(batch.queue(&mut iface, Command::ColumnRange(0, 127))).unwrap();
+        block_on(batch.queue(&mut iface, Command::DisplayOn(true))).unwrap();
+        block_on(batch.send(&mut iface)).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                commands([&[0xAE], &[0x81, 0x10]]),
+                commands([&[0x21, 0, 127], &[0xAF]]),
+            ]
+        );
+        assert!(batch.is_empty());
+
+        batch
+            .push(Command::DisplayOn(false))
+            .unwrap()
+            .push_raw(&[0xB9, 0x30])
+            .unwrap();
+        assert!(matches!(
+            batch.push_raw(&[0xA0, 0x53, 0x00]),
+            Err(Error::BufferTooSmall)
+        ));
+        assert_eq!(batch.as_bytes(), [0xAE, 0xB9, 0x30]);
+    }
+}
@@ -0,0 +1,44 @@
+//! Settings applied when initialising a display
+
+use crate::command::VcomhLevel;
+use crate::Error;
+
+/// Electrical settings sent by the init routine of a display variant. Settings left as `None`
+/// use the default of the variant, which suits most modules.
+///
+/// Usually set through the [`Builder`](crate::Builder), e.g. `.with_contrast(0xCF)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DisplayConfig {
+    /// Contrast, higher is brighter
+    pub contrast: Option<u8>,
+    /// Oscillator frequency and display clock divide ratio, both 0-15
+    pub clock: Option<(u8, u8)>,
+    /// Pre-charge phase 1 and phase 2 periods in display clocks, both 1-15
+    pub precharge: Option<(u8, u8)>,
+    /// VCOMH deselect level. Only used by SH110x and SSD130x controllers, the grayscale
+    /// controllers keep their own VCOMH setting.
+    pub vcomh: Option<VcomhLevel>,
+    /// The panel is supplied by an external VCC, so the internal charge pump or DC-DC converter
+    /// is left off. SSD1322 and SSD1327 have no VCC generator and ignore this.
+    pub external_vcc: bool,
+}
+
+impl DisplayConfig {
+    /// Check that the settings are in range for the controller, or return
+    /// [`Error::InvalidConfig`]
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some((frequency, divide)) = self.clock {
+            if frequency > 0x0F || divide > 0x0F {
+                return Err(Error::InvalidConfig);
+            }
+        }
+
+        if let Some((phase1, phase2)) = self.precharge {
+            if !(1..=0x0F).contains(&phase1) || !(1..=0x0F).contains(&phase2) {
+                return Err(Error::InvalidConfig);
+            }
+        }
+
+        Ok(())
+    }
+}
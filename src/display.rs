@@ -1,26 +1,279 @@
 //! Display variant
 
-use display_interface::{AsyncWriteOnlyDataCommand, DisplayError};
+use display_interface::AsyncWriteOnlyDataCommand;
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{
+    command::{Command, CommandBatch},
+    config::DisplayConfig,
+    displayrotation::DisplayRotation,
+    init::{charge_pump_command, send_init_sequence, InitStep},
+    Error,
+};
+
+/// Pixel format and organisation of the display RAM
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 1 bit per pixel. The RAM is organised in pages of 8 rows and each byte covers 8 rows of a
+    /// single column, with the top row in the least significant bit.
+    Mono,
+    /// 4 bits per pixel. The RAM is organised in rows and each byte covers 2 adjacent pixels of a
+    /// row, with the left pixel in the high nibble.
+    Gray4,
+}
+
+impl PixelFormat {
+    /// Number of bits used by a single pixel
+    pub const fn bits_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Mono => 1,
+            PixelFormat::Gray4 => 4,
+        }
+    }
+}
+
+/// Timing of the reset pulse, in microseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetTiming {
+    /// Time the reset pin is held low
+    pub low_us: u32,
+    /// Time to wait after the reset pin goes high, before the pulse and before the first command
+    pub recovery_us: u32,
+}
 
 /// Trait to represent a speciffic display
 pub trait DisplayVariant {
     /// Width of display
-    const WIDTH: u8;
+    const WIDTH: u16;
     /// Height of display
-    const HEIGHT: u8;
+    const HEIGHT: u16;
     /// Coumn offset
     const COLUMN_OFFSET: u8 = 0;
+    /// Column offset while the segment order is not remapped, in `Rotate90` and `Rotate180`. Only
+    /// differs from `COLUMN_OFFSET` for panels that are not centered on the display RAM.
+    const UNREMAPPED_COLUMN_OFFSET: u8 = Self::COLUMN_OFFSET;
+    /// Number of rows in the display RAM of the controller. Equal to `HEIGHT` when the panel uses
+    /// all of them.
+    const RAM_ROWS: u16 = Self::HEIGHT;
     /// Large Page Address
     const LARGE_PAGE_ADDRESS: bool = false;
+    /// Horizontal addressing mode with column and page ranges. When set, the variant must put the
+    /// controller in horizontal addressing mode during init and a draw area is streamed in a
+    /// single data transfer instead of page by page.
+    const HORIZONTAL_ADDRESSING: bool = false;
+    /// Pixel format of the display RAM
+    const PIXEL_FORMAT: PixelFormat = PixelFormat::Mono;
+
+    /// Reset pulse timing
+    const RESET_TIMING: ResetTiming = ResetTiming {
+        low_us: 10_000,
+        recovery_us: 1_000,
+    };
+
+    /// Number of bytes needed to buffer a whole frame. Monochrome frames take whole pages of 8
+    /// rows, including a partial last page.
+    const BUFFER_SIZE: usize = match Self::PIXEL_FORMAT {
+        PixelFormat::Mono => Self::WIDTH as usize * Self::HEIGHT.div_ceil(8) as usize,
+        PixelFormat::Gray4 => Self::WIDTH as usize * Self::HEIGHT as usize / 2,
+    };
+
+    /// Steps sent by the default `init_column_mode`. Variants that implement `init_column_mode`
+    /// themselves can leave this empty.
+    const INIT_SEQUENCE: &'static [InitStep] = &[];
 
     /// Get integral dimensions from DisplaySize
-    fn dimensions() -> (u8, u8) {
+    fn dimensions() -> (u16, u16) {
         (Self::WIDTH, Self::HEIGHT)
     }
 
-    /// Initialise the display for column mode
+    /// Size of this display. Only differs from `dimensions` for variants selected at runtime,
+    /// whose `WIDTH` and `HEIGHT` are upper bounds.
+    fn size(&self) -> (u16, u16) {
+        Self::dimensions()
+    }
+
+    /// Column offset of this display
+    fn column_offset(&self) -> u8 {
+        Self::COLUMN_OFFSET
+    }
+
+    /// Column offset of this display while the segment order is not remapped
+    fn unremapped_column_offset(&self) -> u8 {
+        Self::UNREMAPPED_COLUMN_OFFSET
+    }
+
+    /// Number of rows in the display RAM of this display's controller
+    fn ram_rows(&self) -> u16 {
+        Self::RAM_ROWS
+    }
+
+    /// Whether this display uses large page addresses
+    fn large_page_address(&self) -> bool {
+        Self::LARGE_PAGE_ADDRESS
+    }
+
+    /// Whether this display uses horizontal addressing mode
+    fn horizontal_addressing(&self) -> bool {
+        Self::HORIZONTAL_ADDRESSING
+    }
+
+    /// Initialise this display for column mode. The default calls `init_column_mode`.
+    #[allow(async_fn_in_trait)]
+    async fn init<DI, D>(
+        &self,
+        iface: &mut DI,
+        delay: &mut D,
+        config: &DisplayConfig,
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        Self::init_column_mode(iface, delay, config).await
+    }
+
+    /// Reset pulse timing of this display
+    fn reset_timing(&self) -> ResetTiming {
+        Self::RESET_TIMING
+    }
+
+    /// Turn the charge pump or DC-DC converter of this display on or off. The default calls
+    /// `set_charge_pump`.
+    #[allow(async_fn_in_trait)]
+    async fn charge_pump<DI>(&self, iface: &mut DI, on: bool) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        Self::set_charge_pump(iface, on).await
+    }
+
+    /// Set the rotation of this display. The default calls `set_rotation`.
+    #[allow(async_fn_in_trait)]
+    async fn rotation<DI>(
+        &self,
+        iface: &mut DI,
+        display_rotation: DisplayRotation,
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        Self::set_rotation(iface, display_rotation).await
+    }
+
+    /// Set the contrast of this display. The default calls `set_contrast`.
+    #[allow(async_fn_in_trait)]
+    async fn contrast<DI>(&self, iface: &mut DI, contrast: u8) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        Self::set_contrast(iface, contrast).await
+    }
+
+    /// Initialise the display for column mode, applying the electrical settings in `config`. The
+    /// default sends [`INIT_SEQUENCE`](Self::INIT_SEQUENCE).
+    #[allow(async_fn_in_trait)]
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        delay: &mut D,
+        config: &DisplayConfig,
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        send_init_sequence(
+            iface,
+            delay,
+            Self::INIT_SEQUENCE,
+            config,
+            Self::dimensions(),
+        )
+        .await
+    }
+
+    /// Set the display rotation. The default uses the segment remap and COM scan direction
+    /// commands of the SH11xx and SSD130x controllers.
+    #[allow(async_fn_in_trait)]
+    async fn set_rotation<DI>(
+        iface: &mut DI,
+        display_rotation: DisplayRotation,
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        let (remap, reverse_com_dir) = match display_rotation {
+            DisplayRotation::Rotate0 => (true, true),
+            DisplayRotation::Rotate90 => (false, true),
+            DisplayRotation::Rotate180 => (false, false),
+            DisplayRotation::Rotate270 => (true, false),
+        };
+
+        CommandBatch::<2>::new()
+            .push(Command::SegmentRemap(remap))?
+            .push(Command::ReverseComDir(reverse_com_dir))?
+            .send(iface)
+            .await
+    }
+
+    /// Turn the internal charge pump or DC-DC converter on or off. The default switches the one
+    /// turned on by [`INIT_SEQUENCE`](Self::INIT_SEQUENCE) and does nothing if there is none.
+    #[allow(async_fn_in_trait)]
+    async fn set_charge_pump<DI>(iface: &mut DI, on: bool) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        match charge_pump_command(Self::INIT_SEQUENCE, on) {
+            Some(command) => command.send(iface).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Set the display contrast
+    #[allow(async_fn_in_trait)]
+    async fn set_contrast<DI>(iface: &mut DI, contrast: u8) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        Command::Contrast(contrast).send(iface).await
+    }
+}
+
+/// Marker trait for display variants whose controller supports continuous horizontal and
+/// vertical hardware scrolling (SSD130x)
+pub trait HardwareScroll: DisplayVariant {}
+
+/// Marker trait for display variants whose controller can additionally scroll the content by a
+/// single column (SSD1309)
+pub trait OneColumnScroll: HardwareScroll {}
+
+/// Trait for display variants with a [`PixelFormat::Gray4`] display RAM
+pub trait GrayscaleVariant: DisplayVariant {
+    /// Number of pixels per column address of the controller. Draw areas start and end on
+    /// multiples of it in display RAM, including the column offset.
+    const COLUMN_UNIT: u16 = 2;
+
+    /// Write nibble packed rows of pixel data to the area from `start` to `end` (exclusive). Both
+    /// are given as (column, row) in pixels, not including the column offset. The columns must be
+    /// aligned to [`COLUMN_UNIT`](Self::COLUMN_UNIT).
+    #[allow(async_fn_in_trait)]
+    async fn draw_area<DI>(
+        iface: &mut DI,
+        start: (u16, u16),
+        end: (u16, u16),
+        data: &[u8],
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand;
+}
+
+/// Trait for grayscale display variants with a programmable gray scale table (SSD132x)
+pub trait GrayScaleTable: GrayscaleVariant {
+    /// Set the drive level of gray levels 1 to 15. `None` selects the built in linear table.
     #[allow(async_fn_in_trait)]
-    async fn init_column_mode<DI>(iface: &mut DI) -> Result<(), DisplayError>
+    async fn set_gray_scale_table<DI>(
+        iface: &mut DI,
+        table: Option<&[u8; 15]>,
+    ) -> Result<(), Error>
     where
         DI: AsyncWriteOnlyDataCommand;
 }
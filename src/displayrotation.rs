@@ -1,7 +1,7 @@
 //! Display rotation
 
 /// Display rotation
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DisplayRotation {
     /// No rotation, normal display
     Rotate0,
@@ -0,0 +1,387 @@
+//! Display variant selected at runtime
+//!
+//! Firmware supporting more than one panel can pick the variant at boot, e.g. from a strap pin.
+//! [`AnyDisplay`] carries the geometry and init sequence of a monochrome variant, while its
+//! `WIDTH` and `HEIGHT` are the largest size it can hold, so a framebuffer sized for
+//! `AnyDisplay` fits every option.
+//!
+//! ```rust,no_run
+//! # use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+//! # struct Interface;
+//! # impl AsyncWriteOnlyDataCommand for Interface {
+//! #     async fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+//! #         Ok(())
+//! #     }
+//! #     async fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! # struct Delay;
+//! # impl embedded_hal_async::delay::DelayNs for Delay {
+//! #     async fn delay_ns(&mut self, _: u32) {}
+//! # }
+//! # async fn example(display_interface: Interface, mut delay: Delay, strap_high: bool) {
+//! use oled_async::{prelude::*, Builder};
+//! use oled_async::displays::{any::AnyDisplay, sh1107::Sh1107_128_128, sh1108::Sh1108_128_160};
+//!
+//! let variant: AnyDisplay<128, 160> = if strap_high {
+//!     AnyDisplay::new(Sh1108_128_160 {})
+//! } else {
+//!     AnyDisplay::new(Sh1107_128_128 {})
+//! };
+//! let mut display: GraphicsMode<_, _, [u8; AnyDisplay::<128, 160>::BUFFER_SIZE]> =
+//!     Builder::new(variant).connect(display_interface).into();
+//! display.init(&mut delay).await.unwrap();
+//! # }
+//! ```
+
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+use embedded_hal_async::delay::DelayNs;
+
+use crate::config::DisplayConfig;
+use crate::display::{DisplayVariant, PixelFormat, ResetTiming};
+use crate::displayrotation::DisplayRotation;
+use crate::init::{charge_pump_command, send_init_sequence, InitStep};
+use crate::Error;
+
+/// Monochrome display variant of up to `MAX_WIDTH` x `MAX_HEIGHT` pixels, chosen at runtime
+#[derive(Debug, Clone, Copy)]
+pub struct AnyDisplay<const MAX_WIDTH: u16 = 160, const MAX_HEIGHT: u16 = 160> {
+    width: u16,
+    height: u16,
+    ram_rows: u16,
+    column_offset: u8,
+    unremapped_column_offset: u8,
+    large_page_address: bool,
+    horizontal_addressing: bool,
+    reset_timing: ResetTiming,
+    init_sequence: &'static [InitStep],
+    record_rotation: fn(DisplayRotation) -> Result<Recording, Error>,
+    record_contrast: fn(u8) -> Result<Recording, Error>,
+}
+
+impl<const MAX_WIDTH: u16, const MAX_HEIGHT: u16> AnyDisplay<MAX_WIDTH, MAX_HEIGHT> {
+    /// Take the geometry and init sequence of `variant`. Fails to build if the variant is larger
+    /// than `MAX_WIDTH` x `MAX_HEIGHT`, is not monochrome, or does not describe its init as an
+    /// [`INIT_SEQUENCE`](DisplayVariant::INIT_SEQUENCE). Rotation and contrast changes send the
+    /// commands of the variant's own `set_rotation` and `set_contrast`.
+    pub fn new<DV: DisplayVariant>(variant: DV) -> Self {
+        const {
+            assert!(
+                matches!(DV::PIXEL_FORMAT, PixelFormat::Mono),
+                "AnyDisplay requires a Mono display variant"
+            );
+            assert!(
+                DV::WIDTH <= MAX_WIDTH && DV::HEIGHT <= MAX_HEIGHT,
+                "display variant is larger than AnyDisplay"
+            );
+            assert!(
+                !DV::INIT_SEQUENCE.is_empty(),
+                "AnyDisplay requires a display variant with an INIT_SEQUENCE"
+            );
+        };
+
+        let _ = variant;
+
+        Self {
+            width: DV::WIDTH,
+            height: DV::HEIGHT,
+            ram_rows: DV::RAM_ROWS,
+            column_offset: DV::COLUMN_OFFSET,
+            unremapped_column_offset: DV::UNREMAPPED_COLUMN_OFFSET,
+            large_page_address: DV::LARGE_PAGE_ADDRESS,
+            horizontal_addressing: DV::HORIZONTAL_ADDRESSING,
+            reset_timing: DV::RESET_TIMING,
+            init_sequence: DV::INIT_SEQUENCE,
+            record_rotation: |rotation| {
+                let mut recording = Recording::default();
+                run_recorded(DV::set_rotation(&mut recording, rotation))?;
+                Ok(recording)
+            },
+            record_contrast: |contrast| {
+                let mut recording = Recording::default();
+                run_recorded(DV::set_contrast(&mut recording, contrast))?;
+                Ok(recording)
+            },
+        }
+    }
+}
+
+/// Run a variant call against a [`Recording`]. Recording never waits, so the call completes on
+/// the first poll. Fails with `Unsupported` if the variant sends more than a `Recording` holds.
+fn run_recorded(call: impl Future<Output = Result<(), Error>>) -> Result<(), Error> {
+    match pin!(call).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(Err(Error::Comm(DisplayError::OutOfBoundsError))) | Poll::Pending => {
+            Err(Error::Unsupported)
+        }
+        Poll::Ready(result) => result,
+    }
+}
+
+/// Interface that stores the transfers a variant sends, to replay them on the real interface
+/// later. Holds up to 4 transfers of 16 bytes in total.
+#[derive(Debug, Clone, Copy, Default)]
+struct Recording {
+    bytes: [u8; 16],
+    /// Length of each transfer and whether it is data rather than commands
+    transfers: [(usize, bool); 4],
+    count: usize,
+}
+
+impl Recording {
+    fn record(&mut self, format: DataFormat<'_>, is_data: bool) -> Result<(), DisplayError> {
+        let DataFormat::U8(bytes) = format else {
+            return Err(DisplayError::DataFormatNotImplemented);
+        };
+        let used: usize = self.transfers[..self.count]
+            .iter()
+            .map(|(len, _)| len)
+            .sum();
+        let transfer = self
+            .transfers
+            .get_mut(self.count)
+            .ok_or(DisplayError::OutOfBoundsError)?;
+        self.bytes
+            .get_mut(used..used + bytes.len())
+            .ok_or(DisplayError::OutOfBoundsError)?
+            .copy_from_slice(bytes);
+        *transfer = (bytes.len(), is_data);
+        self.count += 1;
+
+        Ok(())
+    }
+
+    async fn replay<DI>(&self, iface: &mut DI) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        let mut start = 0;
+        for &(len, is_data) in &self.transfers[..self.count] {
+            let bytes = DataFormat::U8(&self.bytes[start..start + len]);
+            if is_data {
+                iface.send_data(bytes).await?;
+            } else {
+                iface.send_commands(bytes).await?;
+            }
+            start += len;
+        }
+
+        Ok(())
+    }
+}
+
+impl AsyncWriteOnlyDataCommand for Recording {
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(cmd, false)
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(buf, true)
+    }
+}
+
+impl<const MAX_WIDTH: u16, const MAX_HEIGHT: u16> DisplayVariant
+    for AnyDisplay<MAX_WIDTH, MAX_HEIGHT>
+{
+    const WIDTH: u16 = MAX_WIDTH;
+    const HEIGHT: u16 = MAX_HEIGHT;
+
+    fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    fn column_offset(&self) -> u8 {
+        self.column_offset
+    }
+
+    fn ram_rows(&self) -> u16 {
+        self.ram_rows
+    }
+
+    fn unremapped_column_offset(&self) -> u8 {
+        self.unremapped_column_offset
+    }
+
+    fn large_page_address(&self) -> bool {
+        self.large_page_address
+    }
+
+    fn horizontal_addressing(&self) -> bool {
+        self.horizontal_addressing
+    }
+
+    fn reset_timing(&self) -> ResetTiming {
+        self.reset_timing
+    }
+
+    async fn charge_pump<DI>(&self, iface: &mut DI, on: bool) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        match charge_pump_command(self.init_sequence, on) {
+            Some(command) => command.send(iface).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn rotation<DI>(
+        &self,
+        iface: &mut DI,
+        display_rotation: DisplayRotation,
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        (self.record_rotation)(display_rotation)?
+            .replay(iface)
+            .await
+    }
+
+    async fn contrast<DI>(&self, iface: &mut DI, contrast: u8) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        (self.record_contrast)(contrast)?.replay(iface).await
+    }
+
+    async fn init<DI, D>(
+        &self,
+        iface: &mut DI,
+        delay: &mut D,
+        config: &DisplayConfig,
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        send_init_sequence(iface, delay, self.init_sequence, config, self.size()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::displays::{
+        sh1107::Sh1107_128_128, sh1108::Sh1108_128_160, ssd1306::Ssd1306_128_32,
+    };
+    use crate::init::InitStep;
+    use crate::test_helpers::{block_on, command, commands, data, init_transfers, MockInterface};
+    use crate::Command;
+
+    /// Mono variant with its own rotation and contrast commands
+    struct Custom;
+
+    impl DisplayVariant for Custom {
+        const WIDTH: u16 = 64;
+        const HEIGHT: u16 = 32;
+        const INIT_SEQUENCE: &'static [InitStep] = &[InitStep::Command(Command::DisplayOn(true))];
+
+        async fn set_rotation<DI>(
+            iface: &mut DI,
+            display_rotation: DisplayRotation,
+        ) -> Result<(), Error>
+        where
+            DI: AsyncWriteOnlyDataCommand,
+        {
+            iface.send_commands(DataFormat::U8(&[0xF0])).await?;
+            iface
+                .send_data(DataFormat::U8(&[display_rotation as u8]))
+                .await?;
+            Ok(())
+        }
+
+        async fn set_contrast<DI>(iface: &mut DI, contrast: u8) -> Result<(), Error>
+        where
+            DI: AsyncWriteOnlyDataCommand,
+        {
+            iface
+                .send_commands(DataFormat::U8(&[0xF1, contrast]))
+                .await?;
+            Ok(())
+        }
+    }
+
+    /// Mono variant whose rotation sends more than a `Recording` holds
+    struct Verbose;
+
+    impl DisplayVariant for Verbose {
+        const WIDTH: u16 = 64;
+        const HEIGHT: u16 = 32;
+        const INIT_SEQUENCE: &'static [InitStep] = &[InitStep::Command(Command::DisplayOn(true))];
+
+        async fn set_rotation<DI>(iface: &mut DI, _: DisplayRotation) -> Result<(), Error>
+        where
+            DI: AsyncWriteOnlyDataCommand,
+        {
+            iface.send_data(DataFormat::U8(&[0; 32])).await?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn init() {
+        let sh1107: AnyDisplay<128, 160> = AnyDisplay::new(Sh1107_128_128 {});
+        let sh1108: AnyDisplay<128, 160> = AnyDisplay::new(Sh1108_128_160 {});
+        let ssd1306: AnyDisplay<128, 160> = AnyDisplay::new(Ssd1306_128_32 {});
+
+        assert_eq!(init_transfers(sh1107), init_transfers(Sh1107_128_128 {}));
+        assert_eq!(init_transfers(sh1108), init_transfers(Sh1108_128_160 {}));
+        assert_eq!(init_transfers(ssd1306), init_transfers(Ssd1306_128_32 {}));
+    }
+
+    #[test]
+    fn geometry() {
+        let display: AnyDisplay<128, 160> = AnyDisplay::new(Sh1108_128_160 {});
+
+        assert_eq!(display.size(), (128, 160));
+        assert_eq!(display.column_offset(), 16);
+        assert_eq!(display.ram_rows(), 160);
+        assert!(display.large_page_address());
+        assert!(!display.horizontal_addressing());
+        assert_eq!(AnyDisplay::<128, 160>::BUFFER_SIZE, 128 * 160 / 8);
+    }
+
+    #[test]
+    fn forwards_rotation_and_contrast() {
+        let iface = MockInterface::new();
+        let display: AnyDisplay<128, 160> = AnyDisplay::new(Custom);
+
+        block_on(display.rotation(&mut iface.clone(), DisplayRotation::Rotate180)).unwrap();
+        block_on(display.contrast(&mut iface.clone(), 0x42)).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                command(&[0xF0]),
+                data(&[DisplayRotation::Rotate180 as u8]),
+                command(&[0xF1, 0x42]),
+            ]
+        );
+
+        let display: AnyDisplay<128, 160> = AnyDisplay::new(Sh1107_128_128 {});
+
+        block_on(display.rotation(&mut iface.clone(), DisplayRotation::Rotate90)).unwrap();
+        block_on(display.contrast(&mut iface.clone(), 0x42)).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [commands([&[0xA0], &[0xC8]]), command(&[0x81, 0x42])]
+        );
+    }
+
+    #[test]
+    fn rotation_too_long_to_record() {
+        let display: AnyDisplay<128, 160> = AnyDisplay::new(Verbose);
+
+        assert!(matches!(
+            block_on(display.rotation(&mut MockInterface::new(), DisplayRotation::Rotate0)),
+            Err(Error::Unsupported)
+        ));
+    }
+}
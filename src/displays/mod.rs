@@ -3,6 +3,52 @@
 //! This driver can be used in different modes. A mode defines how the driver will behave, and what
 //! methods it exposes. Look at the modes below for more information on what they expose.
 
+pub mod any;
+pub mod sh1106;
 pub mod sh1107;
 pub mod sh1108;
+pub mod sh1122;
+pub mod ssd1306;
 pub mod ssd1309;
+pub mod ssd1322;
+pub mod ssd1327;
+
+use display_interface::AsyncWriteOnlyDataCommand;
+
+use crate::{
+    command::{Command, CommandBatch, VcomhLevel},
+    init::InitStep,
+    Error,
+};
+
+/// Column mode init steps shared by the SH110x controllers. Variants append their display offset
+/// and COM pin configuration.
+pub const SH110X_COLUMN_MODE_INIT: [InitStep; 11] = [
+    InitStep::Command(Command::DisplayOn(false)),
+    InitStep::Clock(0x8, 0x0),
+    InitStep::Multiplex,
+    InitStep::Command(Command::StartLine(0)),
+    // Charge pump or DC-DC converter, depending on the controller.
+    // Display must be off when performing this command
+    InitStep::ChargePump,
+    InitStep::Contrast(0x80),
+    InitStep::PreCharge(0x1, 0xF),
+    InitStep::Vcomh(VcomhLevel::Auto),
+    InitStep::Command(Command::AllOn(false)),
+    InitStep::Command(Command::Invert(false)),
+    InitStep::Command(Command::DisplayOn(true)),
+];
+
+/// Send controller specific commands, each given as its opcode and arguments, in a single
+/// transfer. For controllers that read command arguments with D/C low.
+pub async fn send_batched<DI>(iface: &mut DI, commands: &[(u8, &[u8])]) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    let mut batch = CommandBatch::<64>::new();
+    for (opcode, args) in commands {
+        batch.push_raw(&[*opcode])?.push_raw(args)?;
+    }
+
+    batch.send(iface).await
+}
@@ -0,0 +1,93 @@
+//! SH1106 display variants and specifics
+//!
+//! The SH1106 has 132 columns of display RAM but is almost always mounted on 128 column glass,
+//! centered so that 2 columns of RAM are unused on either side. Because the margin is the same on
+//! both sides the column offset is correct for both segment remap directions, i.e. all display
+//! rotations.
+//!
+//! The SH1106 DC-DC control register (0xAD, 0x8A | on) is the same opcode as
+//! [`Command::ChargePump`], so the shared SH110x init sequence turns the DC-DC converter on.
+
+use crate::command::Command;
+use crate::display::DisplayVariant;
+use crate::displays::SH110X_COLUMN_MODE_INIT;
+use crate::init::{concat, InitStep};
+
+/// Generic 128x64 with SH1106 controller
+#[derive(Debug, Clone, Copy)]
+pub struct Sh1106_128_64 {}
+
+impl DisplayVariant for Sh1106_128_64 {
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 64;
+    const RAM_ROWS: u16 = 64;
+    const COLUMN_OFFSET: u8 = 2;
+
+    const INIT_SEQUENCE: &'static [InitStep] = &concat::<11, 2, 13>(
+        SH110X_COLUMN_MODE_INIT,
+        [
+            InitStep::Command(Command::DisplayOffset(0)),
+            InitStep::Command(Command::ComPinConfig(true)),
+        ],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DisplayConfig;
+    use crate::test_helpers::{commands, init_transfers, init_transfers_with};
+
+    #[test]
+    fn init_128_64() {
+        assert_eq!(
+            init_transfers(Sh1106_128_64 {}),
+            [
+                commands([
+                    &[0xAE],
+                    &[0xD5, 0x80],
+                    &[0xA8, 0x3F],
+                    &[0x40],
+                    &[0xAD, 0x8B],
+                    &[0x81, 0x80],
+                    &[0xD9, 0xF1],
+                    &[0xDB, 0x40],
+                    &[0xA4],
+                    &[0xA6],
+                    &[0xAF],
+                    &[0xD3, 0x00],
+                    &[0xDA, 0x12],
+                ]),
+                commands([&[0xA1], &[0xC8]]),
+            ]
+        );
+    }
+
+    #[test]
+    fn init_external_vcc() {
+        let config = DisplayConfig {
+            external_vcc: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            init_transfers_with(Sh1106_128_64 {}, config)[0],
+            commands([
+                &[0xAE],
+                &[0xD5, 0x80],
+                &[0xA8, 0x3F],
+                &[0x40],
+                // DC-DC converter off
+                &[0xAD, 0x8A],
+                &[0x81, 0x80],
+                &[0xD9, 0xF1],
+                &[0xDB, 0x40],
+                &[0xA4],
+                &[0xA6],
+                &[0xAF],
+                &[0xD3, 0x00],
+                &[0xDA, 0x12],
+            ])
+        );
+    }
+}
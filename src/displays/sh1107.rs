@@ -1,84 +1,135 @@
 //! SH1107 display variants and specifics
 
+use crate::command::Command;
 use crate::display::DisplayVariant;
-use display_interface::{AsyncWriteOnlyDataCommand, DisplayError};
-
-use crate::command::{Command, VcomhLevel};
+use crate::displays::SH110X_COLUMN_MODE_INIT;
+use crate::init::{concat, InitStep};
 
 /// Generic 64x128 with SH1107 controller
-#[derive(Debug, Clone, Copy)]
-pub struct Sh1107_64_128 {}
-
-impl DisplayVariant for Sh1107_64_128 {
-    const WIDTH: u8 = 64;
-    const HEIGHT: u8 = 128;
-    const COLUMN_OFFSET: u8 = 32;
-
-    async fn init_column_mode<DI>(
-        iface: &mut DI,
-        //display_rotation: DisplayRotation,
-    ) -> Result<(), DisplayError>
-    where
-        DI: AsyncWriteOnlyDataCommand,
-    {
-        init_column_mode_common(iface, Self::dimensions()).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
-    }
-}
+pub type Sh1107_64_128 = Sh1107<64, 128, 32>;
 
 /// Generic 128x128 with SH1107 controller
-#[derive(Debug, Clone, Copy)]
-pub struct Sh1107_128_128 {}
+pub type Sh1107_128_128 = Sh1107<128, 128, 0>;
 
-impl DisplayVariant for Sh1107_128_128 {
-    const WIDTH: u8 = 128;
-    const HEIGHT: u8 = 128;
+/// SH1107 panel of any geometry, `W` columns wide starting at RAM column `COL_OFFSET` and `H` rows
+/// tall. The offset applies to the remapped segment order of `Rotate0`, a panel that is not centred
+/// on the display RAM starts at column `128 - W - COL_OFFSET` otherwise. Fails to build if the
+/// panel does not fit the 128x128 display RAM.
+///
+/// ```rust,compile_fail
+/// # use oled_async::{display::DisplayVariant, displays::sh1107::Sh1107};
+/// // 96 columns at offset 48 run past the 128 column display RAM
+/// let width = <Sh1107<96, 128, 48> as DisplayVariant>::WIDTH;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Sh1107<const W: u16, const H: u16, const COL_OFFSET: u8> {}
 
-    async fn init_column_mode<DI>(
-        iface: &mut DI,
-        //display_rotation: DisplayRotation,
-    ) -> Result<(), DisplayError>
-    where
-        DI: AsyncWriteOnlyDataCommand,
-    {
-        init_column_mode_common(iface, Self::dimensions()).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
+impl<const W: u16, const H: u16, const COL_OFFSET: u8> DisplayVariant for Sh1107<W, H, COL_OFFSET> {
+    const WIDTH: u16 = {
+        assert!(
+            W > 0 && W + COL_OFFSET as u16 <= 128,
+            "SH1107 panel columns must fit the 128 column display RAM"
+        );
+        W
+    };
+    const HEIGHT: u16 = {
+        assert!(
+            H >= 8 && H <= 128 && H % 8 == 0,
+            "SH1107 panel height must be a multiple of 8 of at most 128 rows"
+        );
+        H
+    };
+    const RAM_ROWS: u16 = 128;
+    const COLUMN_OFFSET: u8 = COL_OFFSET;
+    const UNREMAPPED_COLUMN_OFFSET: u8 = (128 - Self::WIDTH - COL_OFFSET as u16) as u8;
 
-        Ok(())
-    }
+    const INIT_SEQUENCE: &'static [InitStep] = &concat::<11, 2, 13>(
+        SH110X_COLUMN_MODE_INIT,
+        [
+            InitStep::Command(Command::DisplayOffset(0)),
+            InitStep::Command(Command::ComPinConfig(true)),
+        ],
+    );
 }
 
-/// Initialise the display in column mode (i.e. a byte walks down a column of 8 pixels) with
-/// column 0 on the left and column _(display_width - 1)_ on the right.
-pub async fn init_column_mode_common<DI>(
-    iface: &mut DI,
-    dimensions: (u8, u8),
-) -> Result<(), DisplayError>
-where
-    DI: AsyncWriteOnlyDataCommand,
-{
-    //iface.init().await?;
-    // TODO: Break up into nice bits so display modes can pick whathever they need
-    let (_, display_height) = dimensions;
-
-    Command::DisplayOn(false).send(iface).await?;
-    Command::DisplayClockDiv(0x8, 0x0).send(iface).await?;
-    Command::Multiplex(display_height - 1).send(iface).await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{commands, init_transfers};
 
-    Command::StartLine(0).send(iface).await?;
-    // TODO: Ability to turn charge pump on/off
-    // Display must be off when performing this command
-    Command::ChargePump(true).send(iface).await?;
+    #[test]
+    fn init_64_128() {
+        assert_eq!(
+            init_transfers(Sh1107_64_128 {}),
+            [
+                commands([
+                    &[0xAE],
+                    &[0xD5, 0x80],
+                    &[0xA8, 0x7F],
+                    &[0x40],
+                    &[0xAD, 0x8B],
+                    &[0x81, 0x80],
+                    &[0xD9, 0xF1],
+                    &[0xDB, 0x40],
+                    &[0xA4],
+                    &[0xA6],
+                    &[0xAF],
+                    &[0xD3, 0x00],
+                    &[0xDA, 0x12],
+                ]),
+                commands([&[0xA1], &[0xC8]]),
+            ]
+        );
+    }
 
-    Command::Contrast(0x80).send(iface).await?;
-    Command::PreChargePeriod(0x1, 0xF).send(iface).await?;
-    Command::VcomhDeselect(VcomhLevel::Auto).send(iface).await?;
-    Command::AllOn(false).send(iface).await?;
-    Command::Invert(false).send(iface).await?;
-    Command::DisplayOn(true).send(iface).await?;
+    #[test]
+    fn init_128_128() {
+        assert_eq!(
+            init_transfers(Sh1107_128_128 {}),
+            [
+                commands([
+                    &[0xAE],
+                    &[0xD5, 0x80],
+                    &[0xA8, 0x7F],
+                    &[0x40],
+                    &[0xAD, 0x8B],
+                    &[0x81, 0x80],
+                    &[0xD9, 0xF1],
+                    &[0xDB, 0x40],
+                    &[0xA4],
+                    &[0xA6],
+                    &[0xAF],
+                    &[0xD3, 0x00],
+                    &[0xDA, 0x12],
+                ]),
+                commands([&[0xA1], &[0xC8]]),
+            ]
+        );
+    }
 
-    Ok(())
+    #[test]
+    fn init_generic() {
+        assert_eq!(
+            init_transfers(Sh1107::<80, 96, 24> {})[0],
+            commands([
+                &[0xAE],
+                &[0xD5, 0x80],
+                &[0xA8, 95],
+                &[0x40],
+                &[0xAD, 0x8B],
+                &[0x81, 0x80],
+                &[0xD9, 0xF1],
+                &[0xDB, 0x40],
+                &[0xA4],
+                &[0xA6],
+                &[0xAF],
+                &[0xD3, 0x00],
+                &[0xDA, 0x12],
+            ])
+        );
+        assert_eq!(
+            <Sh1107<80, 96, 24> as DisplayVariant>::BUFFER_SIZE,
+            80 * 96 / 8
+        );
+    }
 }
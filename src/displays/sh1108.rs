@@ -1,33 +1,21 @@
 //! SH1108 display variants and specifics
 
-use crate::display::DisplayVariant;
-use display_interface::{AsyncWriteOnlyDataCommand, DisplayError};
-
 use crate::command::{Command, VcomhLevel};
+use crate::display::DisplayVariant;
+use crate::init::InitStep;
 
 /// Generic 64x160 with SH1108 controller
 #[derive(Debug, Clone, Copy)]
 pub struct Sh1108_64_160 {}
 
 impl DisplayVariant for Sh1108_64_160 {
-    const WIDTH: u8 = 64;
-    const HEIGHT: u8 = 160;
+    const WIDTH: u16 = 64;
+    const HEIGHT: u16 = 160;
+    const RAM_ROWS: u16 = 160;
     const COLUMN_OFFSET: u8 = 48;
     const LARGE_PAGE_ADDRESS: bool = true;
 
-    async fn init_column_mode<DI>(
-        iface: &mut DI,
-        //display_rotation: DisplayRotation,
-    ) -> Result<(), DisplayError>
-    where
-        DI: AsyncWriteOnlyDataCommand,
-    {
-        init_column_mode_common(iface, Self::dimensions(), 0).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
-    }
+    const INIT_SEQUENCE: &'static [InitStep] = &init_sequence(0);
 }
 
 /// Generic 96x160 with SH1108 controller
@@ -35,48 +23,26 @@ impl DisplayVariant for Sh1108_64_160 {
 pub struct Sh1108_96_160 {}
 
 impl DisplayVariant for Sh1108_96_160 {
-    const WIDTH: u8 = 96;
-    const HEIGHT: u8 = 160;
+    const WIDTH: u16 = 96;
+    const HEIGHT: u16 = 160;
+    const RAM_ROWS: u16 = 160;
     const COLUMN_OFFSET: u8 = 32;
     const LARGE_PAGE_ADDRESS: bool = true;
 
-    async fn init_column_mode<DI>(
-        iface: &mut DI,
-        //display_rotation: DisplayRotation,
-    ) -> Result<(), DisplayError>
-    where
-        DI: AsyncWriteOnlyDataCommand,
-    {
-        init_column_mode_common(iface, Self::dimensions(), 1).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
-    }
+    const INIT_SEQUENCE: &'static [InitStep] = &init_sequence(1);
 }
 /// Generic 128x160 with SH1108 controller
 #[derive(Debug, Clone, Copy)]
 pub struct Sh1108_128_160 {}
 
 impl DisplayVariant for Sh1108_128_160 {
-    const WIDTH: u8 = 128;
-    const HEIGHT: u8 = 160;
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 160;
+    const RAM_ROWS: u16 = 160;
     const COLUMN_OFFSET: u8 = 16;
     const LARGE_PAGE_ADDRESS: bool = true;
 
-    async fn init_column_mode<DI>(
-        iface: &mut DI,
-        //display_rotation: DisplayRotation,
-    ) -> Result<(), DisplayError>
-    where
-        DI: AsyncWriteOnlyDataCommand,
-    {
-        init_column_mode_common(iface, Self::dimensions(), 2).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
-    }
+    const INIT_SEQUENCE: &'static [InitStep] = &init_sequence(2);
 }
 
 /// Generic 160x160 with SH1108 controller
@@ -84,59 +50,77 @@ impl DisplayVariant for Sh1108_128_160 {
 pub struct Sh1108_160_160 {}
 
 impl DisplayVariant for Sh1108_160_160 {
-    const WIDTH: u8 = 160;
-    const HEIGHT: u8 = 160;
+    const WIDTH: u16 = 160;
+    const HEIGHT: u16 = 160;
+    const RAM_ROWS: u16 = 160;
     const COLUMN_OFFSET: u8 = 0;
     const LARGE_PAGE_ADDRESS: bool = true;
 
-    async fn init_column_mode<DI>(
-        iface: &mut DI,
-        //display_rotation: DisplayRotation,
-    ) -> Result<(), DisplayError>
-    where
-        DI: AsyncWriteOnlyDataCommand,
-    {
-        init_column_mode_common(iface, Self::dimensions(), 3).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
-    }
+    const INIT_SEQUENCE: &'static [InitStep] = &init_sequence(3);
+}
+
+/// Column mode init steps for the given display resolution setting
+const fn init_sequence(resolution: u8) -> [InitStep; 16] {
+    [
+        InitStep::Command(Command::DisplayOn(false)),
+        InitStep::Clock(0x6, 0x0),
+        InitStep::Command(Command::DisplayResolution(resolution)),
+        InitStep::Command(Command::PreChargePeriod(0x8, 0x2)),
+        InitStep::Command(Command::DisplayOn(true)),
+        InitStep::Multiplex,
+        InitStep::Command(Command::StartLine(0)),
+        // Display must be off when performing this command
+        InitStep::ChargePump,
+        InitStep::Contrast(0x80),
+        InitStep::PreCharge(0x1, 0xF),
+        InitStep::Vcomh(VcomhLevel::Auto),
+        InitStep::Command(Command::AllOn(false)),
+        InitStep::Command(Command::Invert(false)),
+        InitStep::Command(Command::DisplayOn(true)),
+        InitStep::Command(Command::DisplayOffset(0)),
+        InitStep::Command(Command::ComPinConfig(true)),
+    ]
 }
 
-/// Initialise the display in column mode (i.e. a byte walks down a column of 8 pixels) with
-/// column 0 on the left and column _(display_width - 1)_ on the right.
-pub async fn init_column_mode_common<DI>(
-    iface: &mut DI,
-    dimensions: (u8, u8),
-    resolution: u8,
-) -> Result<(), DisplayError>
-where
-    DI: AsyncWriteOnlyDataCommand,
-{
-    //iface.init().await?;
-    // TODO: Break up into nice bits so display modes can pick whathever they need
-    let (_, display_height) = dimensions;
-
-    Command::DisplayOn(false).send(iface).await?;
-    Command::DisplayClockDiv(0x6, 0x0).send(iface).await?;
-    Command::DisplayResolution(resolution).send(iface).await?;
-    Command::PreChargePeriod(0x8, 0x2).send(iface).await?;
-    Command::DisplayOn(true).send(iface).await?;
-
-    Command::Multiplex(display_height - 1).send(iface).await?;
-
-    Command::StartLine(0).send(iface).await?;
-    // TODO: Ability to turn charge pump on/off
-    // Display must be off when performing this command
-    Command::ChargePump(true).send(iface).await?;
-
-    Command::Contrast(0x80).send(iface).await?;
-    Command::PreChargePeriod(0x1, 0xF).send(iface).await?;
-    Command::VcomhDeselect(VcomhLevel::Auto).send(iface).await?;
-    Command::AllOn(false).send(iface).await?;
-    Command::Invert(false).send(iface).await?;
-    Command::DisplayOn(true).send(iface).await?;
-
-    Ok(())
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::test_helpers::{commands, init_transfers, Transfer};
+
+    fn expected_init(resolution: u8) -> Vec<Transfer> {
+        [
+            commands([
+                &[0xAE],
+                &[0xD5, 0x60],
+                &[0xA9, resolution],
+                &[0xD9, 0x28],
+                &[0xAF],
+                &[0xA8, 0x9F],
+                &[0x40],
+                &[0xAD, 0x8B],
+                &[0x81, 0x80],
+                &[0xD9, 0xF1],
+                &[0xDB, 0x40],
+                &[0xA4],
+                &[0xA6],
+                &[0xAF],
+                &[0xD3, 0x00],
+                &[0xDA, 0x12],
+            ]),
+            commands([&[0xA1], &[0xC8]]),
+        ]
+        .to_vec()
+    }
+
+    #[test]
+    fn init() {
+        assert_eq!(init_transfers(Sh1108_64_160 {}), expected_init(0));
+        assert_eq!(init_transfers(Sh1108_96_160 {}), expected_init(1));
+        assert_eq!(init_transfers(Sh1108_128_160 {}), expected_init(2));
+        assert_eq!(init_transfers(Sh1108_160_160 {}), expected_init(3));
+    }
 }
@@ -0,0 +1,177 @@
+//! SH1122 display variants and specifics
+//!
+//! The display RAM is addressed in units of 2 pixels, i.e. one byte per column address. The
+//! row address does not advance on its own, so every row of a draw area is addressed separately.
+
+use crate::config::DisplayConfig;
+use crate::display::{DisplayVariant, GrayscaleVariant, PixelFormat};
+use crate::displays::send_batched;
+use crate::Error;
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat};
+use embedded_hal_async::delay::DelayNs;
+
+/// Generic 256x64 with SH1122 controller
+#[derive(Debug, Clone, Copy)]
+pub struct Sh1122_256_64 {}
+
+impl DisplayVariant for Sh1122_256_64 {
+    const WIDTH: u16 = 256;
+    const HEIGHT: u16 = 64;
+    const PIXEL_FORMAT: PixelFormat = PixelFormat::Gray4;
+
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        _delay: &mut D,
+        config: &DisplayConfig,
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        init_common(iface, config, Self::dimensions()).await
+    }
+
+    async fn set_charge_pump<DI>(iface: &mut DI, on: bool) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        iface
+            .send_commands(DataFormat::U8(&[0xAD, 0x80 | on as u8]))
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl GrayscaleVariant for Sh1122_256_64 {
+    async fn draw_area<DI>(
+        iface: &mut DI,
+        start: (u16, u16),
+        end: (u16, u16),
+        data: &[u8],
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        draw_area_common(iface, Self::COLUMN_OFFSET, start, end, data).await
+    }
+}
+
+/// Initialise the display with row addressing
+pub async fn init_common<DI>(
+    iface: &mut DI,
+    config: &DisplayConfig,
+    dimensions: (u16, u16),
+) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    let (_, display_height) = dimensions;
+    let (fosc, div) = config.clock.unwrap_or((0x5, 0x0));
+    let (phase1, phase2) = config.precharge.unwrap_or((0x2, 0x2));
+
+    send_batched(
+        iface,
+        &[
+            // Display off
+            (0xAE, &[]),
+            // Clock divide ratio and oscillator frequency
+            (0xD5, &[((0xF & fosc) << 4) | (0xF & div)]),
+            // Multiplex ratio
+            (0xA8, &[(display_height - 1) as u8]),
+            // Display offset and start line
+            (0xD3, &[0x00]),
+            (0x40, &[]),
+            // Built-in DC-DC converter, unless VCC is supplied externally. Display must be off
+            // when performing this command
+            (0xAD, &[0x80 | !config.external_vcc as u8]),
+            (0x81, &[config.contrast.unwrap_or(0x80)]),
+            // Phase length
+            (0xD9, &[((0xF & phase2) << 4) | (0xF & phase1)]),
+            // VCOM deselect and VSEGM levels
+            (0xDB, &[0x35]),
+            (0xDC, &[0x35]),
+            // Discharge VSL level
+            (0x30, &[]),
+            // Normal display, not inverted
+            (0xA4, &[]),
+            (0xA6, &[]),
+            // Display on
+            (0xAF, &[]),
+        ],
+    )
+    .await
+}
+
+/// Write nibble packed rows to the area from `start` to `end` (exclusive), addressing each row
+/// separately. Columns must be multiples of 2.
+pub async fn draw_area_common<DI>(
+    iface: &mut DI,
+    column_offset: u8,
+    start: (u16, u16),
+    end: (u16, u16),
+    data: &[u8],
+) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    let column = ((u16::from(column_offset) + start.0) / 2) as u8;
+    let row_length = usize::from((end.0 - start.0) / 2);
+
+    for (row, data) in (start.1..end.1).zip(data.chunks(row_length)) {
+        iface
+            .send_commands(DataFormat::U8(&[
+                0xB0,
+                row as u8,
+                0x10 | (0x7 & (column >> 4)),
+                0xF & column,
+            ]))
+            .await?;
+        iface.send_data(DataFormat::U8(data)).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{block_on, command, commands, data, init_transfers, MockInterface};
+
+    #[test]
+    fn init_256_64() {
+        assert_eq!(
+            init_transfers(Sh1122_256_64 {}),
+            [
+                command(&[
+                    0xAE, 0xD5, 0x50, 0xA8, 0x3F, 0xD3, 0x00, 0x40, 0xAD, 0x81, 0x81, 0x80, 0xD9,
+                    0x22, 0xDB, 0x35, 0xDC, 0x35, 0x30, 0xA4, 0xA6, 0xAF
+                ]),
+                commands([&[0xA1], &[0xC8]]),
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_area_addresses_each_row() {
+        let mut iface = MockInterface::new();
+
+        block_on(Sh1122_256_64::draw_area(
+            &mut iface,
+            (64, 10),
+            (68, 12),
+            &[0x01, 0x23, 0x45, 0x67],
+        ))
+        .unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                command(&[0xB0, 10, 0x12, 0x00]),
+                data(&[0x01, 0x23]),
+                command(&[0xB0, 11, 0x12, 0x00]),
+                data(&[0x45, 0x67]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! SSD1306 display variants and specifics
+//!
+//! Panels narrower than the 128 column display RAM are centered on it, so the column offset is
+//! correct for both segment remap directions. The glass of 96x16 modules sits on SEG32 to SEG127
+//! instead, so its column offset depends on the segment remap.
+
+use crate::command::{AddressingMode, Command, VcomhLevel};
+use crate::display::{DisplayVariant, HardwareScroll};
+use crate::init::InitStep;
+
+/// Generic 128x64 with SSD1306 controller
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1306_128_64 {}
+
+impl DisplayVariant for Ssd1306_128_64 {
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 64;
+    const RAM_ROWS: u16 = 64;
+    const HORIZONTAL_ADDRESSING: bool = true;
+
+    const INIT_SEQUENCE: &'static [InitStep] = &init_sequence(true);
+}
+
+impl HardwareScroll for Ssd1306_128_64 {}
+
+/// Generic 128x32 with SSD1306 controller
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1306_128_32 {}
+
+impl DisplayVariant for Ssd1306_128_32 {
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 32;
+    const RAM_ROWS: u16 = 64;
+    const HORIZONTAL_ADDRESSING: bool = true;
+
+    const INIT_SEQUENCE: &'static [InitStep] = &init_sequence(false);
+}
+
+impl HardwareScroll for Ssd1306_128_32 {}
+
+/// Generic 96x16 with SSD1306 controller
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1306_96_16 {}
+
+impl DisplayVariant for Ssd1306_96_16 {
+    const WIDTH: u16 = 96;
+    const HEIGHT: u16 = 16;
+    const RAM_ROWS: u16 = 64;
+    const UNREMAPPED_COLUMN_OFFSET: u8 = 32;
+    const HORIZONTAL_ADDRESSING: bool = true;
+
+    const INIT_SEQUENCE: &'static [InitStep] = &init_sequence(false);
+}
+
+impl HardwareScroll for Ssd1306_96_16 {}
+
+/// Generic 72x40 with SSD1306 controller
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1306_72_40 {}
+
+impl DisplayVariant for Ssd1306_72_40 {
+    const WIDTH: u16 = 72;
+    const HEIGHT: u16 = 40;
+    const RAM_ROWS: u16 = 64;
+    const COLUMN_OFFSET: u8 = 28;
+    const HORIZONTAL_ADDRESSING: bool = true;
+
+    const INIT_SEQUENCE: &'static [InitStep] = &init_sequence(true);
+}
+
+impl HardwareScroll for Ssd1306_72_40 {}
+
+/// Generic 64x48 with SSD1306 controller
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1306_64_48 {}
+
+impl DisplayVariant for Ssd1306_64_48 {
+    const WIDTH: u16 = 64;
+    const HEIGHT: u16 = 48;
+    const RAM_ROWS: u16 = 64;
+    const COLUMN_OFFSET: u8 = 32;
+    const HORIZONTAL_ADDRESSING: bool = true;
+
+    const INIT_SEQUENCE: &'static [InitStep] = &init_sequence(true);
+}
+
+impl HardwareScroll for Ssd1306_64_48 {}
+
+/// Column mode init steps with horizontal addressing, for sequential or alternative COM pins
+const fn init_sequence(alternative_com_pins: bool) -> [InitStep; 14] {
+    [
+        InitStep::Command(Command::DisplayOn(false)),
+        InitStep::Clock(0x8, 0x0),
+        InitStep::Multiplex,
+        InitStep::Command(Command::StartLine(0)),
+        // Display must be off when performing this command
+        InitStep::SsdChargePump,
+        // Draw areas are sent as column and page ranges
+        InitStep::Command(Command::MemoryAddressingMode(AddressingMode::Horizontal)),
+        InitStep::Contrast(0x80),
+        InitStep::PreCharge(0x1, 0xF),
+        InitStep::Vcomh(VcomhLevel::Auto),
+        InitStep::Command(Command::AllOn(false)),
+        InitStep::Command(Command::Invert(false)),
+        InitStep::Command(Command::DisplayOn(true)),
+        InitStep::Command(Command::DisplayOffset(0)),
+        InitStep::Command(Command::ComPinConfig(alternative_com_pins)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::config::DisplayConfig;
+    use crate::displayrotation::DisplayRotation;
+    use crate::properties::DisplayProperties;
+    use crate::test_helpers::{
+        block_on, commands, init_transfers, init_transfers_with, MockInterface, Transfer,
+    };
+
+    fn expected_init(multiplex: u8, com_pins: u8) -> Vec<Transfer> {
+        [
+            commands([
+                &[0xAE],
+                &[0xD5, 0x80],
+                &[0xA8, multiplex],
+                &[0x40],
+                &[0x8D, 0x14],
+                &[0x20, 0x00],
+                &[0x81, 0x80],
+                &[0xD9, 0xF1],
+                &[0xDB, 0x40],
+                &[0xA4],
+                &[0xA6],
+                &[0xAF],
+                &[0xD3, 0x00],
+                &[0xDA, com_pins],
+            ]),
+            commands([&[0xA1], &[0xC8]]),
+        ]
+        .to_vec()
+    }
+
+    #[test]
+    fn init() {
+        assert_eq!(init_transfers(Ssd1306_128_64 {}), expected_init(63, 0x12));
+        assert_eq!(init_transfers(Ssd1306_128_32 {}), expected_init(31, 0x02));
+        assert_eq!(init_transfers(Ssd1306_96_16 {}), expected_init(15, 0x02));
+        assert_eq!(init_transfers(Ssd1306_72_40 {}), expected_init(39, 0x12));
+        assert_eq!(init_transfers(Ssd1306_64_48 {}), expected_init(47, 0x12));
+    }
+
+    #[test]
+    fn init_with_config() {
+        let config = DisplayConfig {
+            contrast: Some(0x9F),
+            clock: Some((0xF, 0x1)),
+            precharge: Some((0x2, 0x2)),
+            vcomh: Some(VcomhLevel::V083),
+            external_vcc: true,
+        };
+
+        assert_eq!(
+            init_transfers_with(Ssd1306_128_64 {}, config)[0],
+            commands([
+                &[0xAE],
+                &[0xD5, 0xF1],
+                &[0xA8, 63],
+                &[0x40],
+                &[0x8D, 0x10],
+                &[0x20, 0x00],
+                &[0x81, 0x9F],
+                &[0xD9, 0x22],
+                &[0xDB, 0x30],
+                &[0xA4],
+                &[0xA6],
+                &[0xAF],
+                &[0xD3, 0x00],
+                &[0xDA, 0x12],
+            ])
+        );
+    }
+
+    #[test]
+    fn column_offset_follows_segment_remap() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Ssd1306_96_16 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+
+        for (rotation, offset) in [
+            (DisplayRotation::Rotate0, 0),
+            (DisplayRotation::Rotate90, 32),
+            (DisplayRotation::Rotate180, 32),
+            (DisplayRotation::Rotate270, 0),
+        ] {
+            block_on(properties.set_rotation(rotation)).unwrap();
+            assert_eq!(properties.column_offset(), offset);
+        }
+
+        block_on(properties.set_rotation(DisplayRotation::Rotate180)).unwrap();
+        iface.take();
+        block_on(properties.set_draw_area((32, 0), (128, 2))).unwrap();
+        assert_eq!(iface.take(), [commands([&[0x21, 32, 127], &[0x22, 0, 1]])]);
+    }
+}
@@ -1,61 +1,126 @@
 //! SSD1309 display variants and specifics
 
-use crate::display::DisplayVariant;
-use display_interface::{AsyncWriteOnlyDataCommand, DisplayError};
+use crate::command::{AddressingMode, Command, VcomhLevel};
+use crate::display::{DisplayVariant, HardwareScroll, OneColumnScroll};
+use crate::init::InitStep;
 
-use crate::command::{Command, VcomhLevel};
+/// Generic 128x64 with SSD1309 controller
+pub type Ssd1309_128_64 = Ssd1309<128, 64, 0>;
 
-/// Generic 128x128 with SSD1309 controller
+/// SSD1309 panel of any geometry, `W` columns wide starting at RAM column `OFFSET` and `H` rows
+/// tall. The offset applies to the remapped segment order of `Rotate0`, a panel that is not centred
+/// on the display RAM starts at column `128 - W - OFFSET` otherwise. Fails to build if the panel
+/// does not fit the 128x64 display RAM.
+///
+/// ```rust,compile_fail
+/// # use oled_async::{display::DisplayVariant, displays::ssd1309::Ssd1309};
+/// // The display RAM has 64 rows
+/// let height = <Ssd1309<128, 80, 0> as DisplayVariant>::HEIGHT;
+/// ```
 #[derive(Debug, Clone, Copy)]
-pub struct Ssd1309_128_64 {}
-
-impl DisplayVariant for Ssd1309_128_64 {
-    const WIDTH: u8 = 128;
-    const HEIGHT: u8 = 64;
-
-    async fn init_column_mode<DI>(
-        iface: &mut DI,
-        //display_rotation: DisplayRotation,
-    ) -> Result<(), DisplayError>
-    where
-        DI: AsyncWriteOnlyDataCommand,
-    {
-        init_column_mode_common(iface, Self::dimensions()).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
-    }
+pub struct Ssd1309<const W: u16, const H: u16, const OFFSET: u8> {}
+
+impl<const W: u16, const H: u16, const OFFSET: u8> DisplayVariant for Ssd1309<W, H, OFFSET> {
+    const WIDTH: u16 = {
+        assert!(
+            W > 0 && W + OFFSET as u16 <= 128,
+            "SSD1309 panel columns must fit the 128 column display RAM"
+        );
+        W
+    };
+    const HEIGHT: u16 = {
+        assert!(
+            H >= 16 && H <= 64 && H % 8 == 0,
+            "SSD1309 panel height must be a multiple of 8 from 16 to 64 rows"
+        );
+        H
+    };
+    const RAM_ROWS: u16 = 64;
+    const COLUMN_OFFSET: u8 = OFFSET;
+    const UNREMAPPED_COLUMN_OFFSET: u8 = (128 - Self::WIDTH - OFFSET as u16) as u8;
+    const HORIZONTAL_ADDRESSING: bool = true;
+
+    // Panels taller than 32 rows are wired to alternative COM pins, as on the SSD1306
+    const INIT_SEQUENCE: &'static [InitStep] = &init_sequence(H > 32);
 }
 
-/// Initialise the display in column mode (i.e. a byte walks down a column of 8 pixels) with
-/// column 0 on the left and column _(display_width - 1)_ on the right.
-pub async fn init_column_mode_common<DI>(
-    iface: &mut DI,
-    dimensions: (u8, u8),
-) -> Result<(), DisplayError>
-where
-    DI: AsyncWriteOnlyDataCommand,
-{
-    //iface.init().await?;
-    // TODO: Break up into nice bits so display modes can pick whathever they need
-    let (_, display_height) = dimensions;
-
-    Command::DisplayOn(false).send(iface).await?;
-    Command::DisplayClockDiv(0x8, 0x0).send(iface).await?;
-    Command::Multiplex(display_height - 1).send(iface).await?;
-
-    Command::StartLine(0).send(iface).await?;
-    // TODO: Ability to turn charge pump on/off
-    // Display must be off when performing this command
-    Command::ChargePump(true).send(iface).await?;
-
-    Command::Contrast(0x80).send(iface).await?;
-    Command::PreChargePeriod(0x1, 0xF).send(iface).await?;
-    Command::VcomhDeselect(VcomhLevel::Auto).send(iface).await?;
-    Command::AllOn(false).send(iface).await?;
-    Command::Invert(false).send(iface).await?;
-    Command::DisplayOn(true).send(iface).await?;
-
-    Ok(())
+impl<const W: u16, const H: u16, const OFFSET: u8> HardwareScroll for Ssd1309<W, H, OFFSET> {}
+
+impl<const W: u16, const H: u16, const OFFSET: u8> OneColumnScroll for Ssd1309<W, H, OFFSET> {}
+
+/// Column mode init steps with horizontal addressing, for sequential or alternative COM pins
+const fn init_sequence(alternative_com_pins: bool) -> [InitStep; 14] {
+    [
+        InitStep::Command(Command::DisplayOn(false)),
+        InitStep::Clock(0x8, 0x0),
+        InitStep::Multiplex,
+        InitStep::Command(Command::StartLine(0)),
+        // Display must be off when performing this command
+        InitStep::ChargePump,
+        // Draw areas are sent as column and page ranges
+        InitStep::Command(Command::MemoryAddressingMode(AddressingMode::Horizontal)),
+        InitStep::Contrast(0x80),
+        InitStep::PreCharge(0x1, 0xF),
+        InitStep::Vcomh(VcomhLevel::Auto),
+        InitStep::Command(Command::AllOn(false)),
+        InitStep::Command(Command::Invert(false)),
+        InitStep::Command(Command::DisplayOn(true)),
+        InitStep::Command(Command::DisplayOffset(0)),
+        InitStep::Command(Command::ComPinConfig(alternative_com_pins)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{commands, init_transfers};
+
+    #[test]
+    fn init_128_64() {
+        assert_eq!(
+            init_transfers(Ssd1309_128_64 {}),
+            [
+                commands([
+                    &[0xAE],
+                    &[0xD5, 0x80],
+                    &[0xA8, 0x3F],
+                    &[0x40],
+                    &[0xAD, 0x8B],
+                    &[0x20, 0x00],
+                    &[0x81, 0x80],
+                    &[0xD9, 0xF1],
+                    &[0xDB, 0x40],
+                    &[0xA4],
+                    &[0xA6],
+                    &[0xAF],
+                    &[0xD3, 0x00],
+                    &[0xDA, 0x12],
+                ]),
+                commands([&[0xA1], &[0xC8]]),
+            ]
+        );
+    }
+
+    #[test]
+    fn init_generic() {
+        assert_eq!(
+            init_transfers(Ssd1309::<96, 32, 16> {})[0],
+            commands([
+                &[0xAE],
+                &[0xD5, 0x80],
+                &[0xA8, 31],
+                &[0x40],
+                &[0xAD, 0x8B],
+                &[0x20, 0x00],
+                &[0x81, 0x80],
+                &[0xD9, 0xF1],
+                &[0xDB, 0x40],
+                &[0xA4],
+                &[0xA6],
+                &[0xAF],
+                &[0xD3, 0x00],
+                &[0xDA, 0x02],
+            ])
+        );
+    }
 }
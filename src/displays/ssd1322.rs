@@ -0,0 +1,340 @@
+//! SSD1322 display variants and specifics
+//!
+//! Unlike most other controllers the SSD1322 takes command arguments as data bytes, i.e. with
+//! D/C high. Its 480 column display RAM is addressed in units of 4 pixels.
+
+use crate::config::DisplayConfig;
+use crate::display::{DisplayVariant, GrayScaleTable, GrayscaleVariant, PixelFormat};
+use crate::displayrotation::DisplayRotation;
+use crate::Error;
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat};
+use embedded_hal_async::delay::DelayNs;
+
+/// Generic 256x64 with SSD1322 controller
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1322_256_64 {}
+
+impl DisplayVariant for Ssd1322_256_64 {
+    const WIDTH: u16 = 256;
+    const HEIGHT: u16 = 64;
+    // Column address 0x1C
+    const COLUMN_OFFSET: u8 = 0x1C * 4;
+    const PIXEL_FORMAT: PixelFormat = PixelFormat::Gray4;
+
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        _delay: &mut D,
+        config: &DisplayConfig,
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        init_common(iface, config, Self::dimensions()).await
+    }
+
+    async fn set_rotation<DI>(
+        iface: &mut DI,
+        display_rotation: DisplayRotation,
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        set_rotation_common(iface, display_rotation).await
+    }
+
+    async fn set_contrast<DI>(iface: &mut DI, contrast: u8) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        send(iface, 0xC1, &[contrast]).await
+    }
+}
+
+impl GrayscaleVariant for Ssd1322_256_64 {
+    const COLUMN_UNIT: u16 = 4;
+
+    async fn draw_area<DI>(
+        iface: &mut DI,
+        start: (u16, u16),
+        end: (u16, u16),
+        data: &[u8],
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        draw_area_common(iface, Self::COLUMN_OFFSET, start, end, data).await
+    }
+}
+
+impl GrayScaleTable for Ssd1322_256_64 {
+    async fn set_gray_scale_table<DI>(iface: &mut DI, table: Option<&[u8; 15]>) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        set_gray_scale_table_common(iface, table).await
+    }
+}
+
+/// Send a command followed by its arguments as data
+async fn send<DI>(iface: &mut DI, command: u8, args: &[u8]) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    iface.send_commands(DataFormat::U8(&[command])).await?;
+    if !args.is_empty() {
+        iface.send_data(DataFormat::U8(args)).await?;
+    }
+
+    Ok(())
+}
+
+/// Send commands, each given as its opcode and arguments. The SSD1322 reads arguments as data,
+/// so every command takes its own transfers.
+async fn send_each<DI>(iface: &mut DI, commands: &[(u8, &[u8])]) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    for (command, args) in commands {
+        send(iface, *command, args).await?;
+    }
+
+    Ok(())
+}
+
+/// Initialise the display with row addressing and horizontal address increment
+pub async fn init_common<DI>(
+    iface: &mut DI,
+    config: &DisplayConfig,
+    dimensions: (u16, u16),
+) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    let (_, display_height) = dimensions;
+    let (fosc, div) = config.clock.unwrap_or((0x9, 0x1));
+    let (phase1, phase2) = config.precharge.unwrap_or((0x2, 0xE));
+
+    send_each(
+        iface,
+        &[
+            // Unlock the command interface
+            (0xFD, &[0x12]),
+            // Display off
+            (0xAE, &[]),
+            // Clock divide ratio and oscillator frequency
+            (0xB3, &[((0xF & fosc) << 4) | (0xF & div)]),
+            // Multiplex ratio
+            (0xCA, &[(display_height - 1) as u8]),
+            // Display offset and start line
+            (0xA2, &[0x00]),
+            (0xA1, &[0x00]),
+            // Disable GPIO
+            (0xB5, &[0x00]),
+            // Internal VDD regulator
+            (0xAB, &[0x01]),
+            // External VSL, enhanced low gray scale display quality
+            (0xB4, &[0xA0, 0xFD]),
+            (0xC1, &[config.contrast.unwrap_or(0x80)]),
+            // Master contrast current
+            (0xC7, &[0x0F]),
+            // Linear gray scale table
+            (0xB9, &[]),
+            // Phase length
+            (0xB1, &[((0xF & phase2) << 4) | (0xF & phase1)]),
+            // Display enhancement B
+            (0xD1, &[0xA2, 0x20]),
+            // Pre-charge voltage, second pre-charge period and VCOMH
+            (0xBB, &[0x1F]),
+            (0xB6, &[0x08]),
+            (0xBE, &[0x07]),
+            // Normal display, exit partial display mode
+            (0xA6, &[]),
+            (0xA9, &[]),
+            // Display on
+            (0xAF, &[]),
+        ],
+    )
+    .await
+}
+
+/// Set the display rotation through the re-map and dual COM line mode register
+pub async fn set_rotation_common<DI>(
+    iface: &mut DI,
+    display_rotation: DisplayRotation,
+) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    // Nibble re-map is always enabled so the left pixel is in the high nibble. Bit 1 is the
+    // column address re-map and bit 4 the COM scan direction.
+    let remap = match display_rotation {
+        DisplayRotation::Rotate0 => 0x14,
+        DisplayRotation::Rotate90 => 0x16,
+        DisplayRotation::Rotate180 => 0x06,
+        DisplayRotation::Rotate270 => 0x04,
+    };
+
+    send(iface, 0xA0, &[remap, 0x11]).await
+}
+
+/// Write nibble packed rows to the area from `start` to `end` (exclusive). Columns must be
+/// multiples of 4.
+pub async fn draw_area_common<DI>(
+    iface: &mut DI,
+    column_offset: u8,
+    start: (u16, u16),
+    end: (u16, u16),
+    data: &[u8],
+) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    let column_offset = u16::from(column_offset);
+    let start_column = ((column_offset + start.0) / 4) as u8;
+    let end_column = ((column_offset + end.0) / 4 - 1) as u8;
+
+    send(iface, 0x15, &[start_column, end_column]).await?;
+    send(iface, 0x75, &[start.1 as u8, (end.1 - 1) as u8]).await?;
+    // Write RAM
+    send(iface, 0x5C, &[]).await?;
+
+    iface.send_data(DataFormat::U8(data)).await?;
+
+    Ok(())
+}
+
+/// Set the drive level of gray levels 1 to 15, or select the linear table if `table` is `None`
+pub async fn set_gray_scale_table_common<DI>(
+    iface: &mut DI,
+    table: Option<&[u8; 15]>,
+) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    match table {
+        Some(table) => {
+            send(iface, 0xB8, table).await?;
+            // Enable the gray scale table
+            send(iface, 0x00, &[]).await
+        }
+        None => send(iface, 0xB9, &[]).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{
+        block_on, command, data, init_transfers, init_transfers_with, MockInterface,
+    };
+
+    #[test]
+    fn init_256_64() {
+        assert_eq!(
+            init_transfers(Ssd1322_256_64 {}),
+            [
+                command(&[0xFD]),
+                data(&[0x12]),
+                command(&[0xAE]),
+                command(&[0xB3]),
+                data(&[0x91]),
+                command(&[0xCA]),
+                data(&[0x3F]),
+                command(&[0xA2]),
+                data(&[0x00]),
+                command(&[0xA1]),
+                data(&[0x00]),
+                command(&[0xB5]),
+                data(&[0x00]),
+                command(&[0xAB]),
+                data(&[0x01]),
+                command(&[0xB4]),
+                data(&[0xA0, 0xFD]),
+                command(&[0xC1]),
+                data(&[0x80]),
+                command(&[0xC7]),
+                data(&[0x0F]),
+                command(&[0xB9]),
+                command(&[0xB1]),
+                data(&[0xE2]),
+                command(&[0xD1]),
+                data(&[0xA2, 0x20]),
+                command(&[0xBB]),
+                data(&[0x1F]),
+                command(&[0xB6]),
+                data(&[0x08]),
+                command(&[0xBE]),
+                data(&[0x07]),
+                command(&[0xA6]),
+                command(&[0xA9]),
+                command(&[0xAF]),
+                command(&[0xA0]),
+                data(&[0x14, 0x11]),
+            ]
+        );
+    }
+
+    #[test]
+    fn init_with_config() {
+        let config = DisplayConfig {
+            contrast: Some(0xFF),
+            clock: Some((0x5, 0x0)),
+            precharge: Some((0x4, 0x8)),
+            ..Default::default()
+        };
+        let transfers = init_transfers_with(Ssd1322_256_64 {}, config);
+
+        assert_eq!(transfers[4], data(&[0x50]));
+        assert_eq!(transfers[18], data(&[0xFF]));
+        assert_eq!(transfers[23], data(&[0x84]));
+    }
+
+    #[test]
+    fn draw_area() {
+        let mut iface = MockInterface::new();
+
+        block_on(Ssd1322_256_64::draw_area(
+            &mut iface,
+            (8, 2),
+            (16, 4),
+            &[0x12; 8],
+        ))
+        .unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                command(&[0x15]),
+                data(&[0x1E, 0x1F]),
+                command(&[0x75]),
+                data(&[2, 3]),
+                command(&[0x5C]),
+                data(&[0x12; 8]),
+            ]
+        );
+    }
+
+    #[test]
+    fn gray_scale_table() {
+        let mut iface = MockInterface::new();
+        let table = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+        block_on(Ssd1322_256_64::set_gray_scale_table(
+            &mut iface,
+            Some(&table),
+        ))
+        .unwrap();
+        block_on(Ssd1322_256_64::set_gray_scale_table(&mut iface, None)).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                command(&[0xB8]),
+                data(&table),
+                command(&[0x00]),
+                command(&[0xB9]),
+            ]
+        );
+    }
+}
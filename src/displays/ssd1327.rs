@@ -0,0 +1,269 @@
+//! SSD1327 display variants and specifics
+//!
+//! The display RAM is addressed in units of 2 pixels, i.e. one byte per column address.
+
+use crate::config::DisplayConfig;
+use crate::display::{DisplayVariant, GrayScaleTable, GrayscaleVariant, PixelFormat};
+use crate::displayrotation::DisplayRotation;
+use crate::displays::send_batched;
+use crate::Error;
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat};
+use embedded_hal_async::delay::DelayNs;
+
+/// Generic 128x128 with SSD1327 controller
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1327_128_128 {}
+
+impl DisplayVariant for Ssd1327_128_128 {
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 128;
+    const PIXEL_FORMAT: PixelFormat = PixelFormat::Gray4;
+
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        _delay: &mut D,
+        config: &DisplayConfig,
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        init_common(iface, config, Self::dimensions()).await
+    }
+
+    async fn set_rotation<DI>(
+        iface: &mut DI,
+        display_rotation: DisplayRotation,
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        set_rotation_common(iface, display_rotation).await
+    }
+}
+
+impl GrayscaleVariant for Ssd1327_128_128 {
+    async fn draw_area<DI>(
+        iface: &mut DI,
+        start: (u16, u16),
+        end: (u16, u16),
+        data: &[u8],
+    ) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        draw_area_common(iface, Self::COLUMN_OFFSET, start, end, data).await
+    }
+}
+
+impl GrayScaleTable for Ssd1327_128_128 {
+    async fn set_gray_scale_table<DI>(iface: &mut DI, table: Option<&[u8; 15]>) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        set_gray_scale_table_common(iface, table).await
+    }
+}
+
+/// Initialise the display with row addressing and horizontal address increment
+pub async fn init_common<DI>(
+    iface: &mut DI,
+    config: &DisplayConfig,
+    dimensions: (u16, u16),
+) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    let (_, display_height) = dimensions;
+    let (fosc, div) = config.clock.unwrap_or((0x0, 0x0));
+    let (phase1, phase2) = config.precharge.unwrap_or((0x1, 0xF));
+
+    send_batched(
+        iface,
+        &[
+            // Display off
+            (0xAE, &[]),
+            // Start line and display offset
+            (0xA1, &[0x00]),
+            (0xA2, &[0x00]),
+            // Normal display
+            (0xA4, &[]),
+            // Multiplex ratio
+            (0xA8, &[(display_height - 1) as u8]),
+            // Internal VDD regulator
+            (0xAB, &[0x01]),
+            // Phase length
+            (0xB1, &[((0xF & phase2) << 4) | (0xF & phase1)]),
+            // Clock divide ratio and oscillator frequency
+            (0xB3, &[((0xF & fosc) << 4) | (0xF & div)]),
+            // Pre-charge voltage and VCOMH
+            (0xBC, &[0x08]),
+            (0xBE, &[0x07]),
+            // Enable second pre-charge and internal VSL
+            (0xD5, &[0x62]),
+            (0xB6, &[0x0F]),
+            // Linear gray scale table
+            (0xB9, &[]),
+            (0x81, &[config.contrast.unwrap_or(0x80)]),
+            // Display on
+            (0xAF, &[]),
+        ],
+    )
+    .await
+}
+
+/// Set the display rotation through the re-map register
+pub async fn set_rotation_common<DI>(
+    iface: &mut DI,
+    display_rotation: DisplayRotation,
+) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    // COM split odd/even is always enabled. Bit 0 is the column address re-map and bit 4 the COM
+    // re-map. The column address re-map mirrors whole column addresses, i.e. pixel pairs, so the
+    // nibble re-map (bit 1) is set exactly when the column address re-map is not. That keeps the
+    // left pixel of each pair in the high nibble in either segment direction.
+    let remap = match display_rotation {
+        DisplayRotation::Rotate0 => 0x51,
+        DisplayRotation::Rotate90 => 0x52,
+        DisplayRotation::Rotate180 => 0x42,
+        DisplayRotation::Rotate270 => 0x41,
+    };
+
+    iface.send_commands(DataFormat::U8(&[0xA0, remap])).await?;
+
+    Ok(())
+}
+
+/// Write nibble packed rows to the area from `start` to `end` (exclusive). Columns must be
+/// multiples of 2.
+pub async fn draw_area_common<DI>(
+    iface: &mut DI,
+    column_offset: u8,
+    start: (u16, u16),
+    end: (u16, u16),
+    data: &[u8],
+) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    let column_offset = u16::from(column_offset);
+    let start_column = ((column_offset + start.0) / 2) as u8;
+    let end_column = ((column_offset + end.0) / 2 - 1) as u8;
+
+    iface
+        .send_commands(DataFormat::U8(&[
+            0x15,
+            start_column,
+            end_column,
+            0x75,
+            start.1 as u8,
+            (end.1 - 1) as u8,
+        ]))
+        .await?;
+
+    iface.send_data(DataFormat::U8(data)).await?;
+
+    Ok(())
+}
+
+/// Set the drive level of gray levels 1 to 15, or select the linear table if `table` is `None`
+pub async fn set_gray_scale_table_common<DI>(
+    iface: &mut DI,
+    table: Option<&[u8; 15]>,
+) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    match table {
+        Some(table) => {
+            let mut data = [0xB8; 16];
+            data[1..].copy_from_slice(table);
+
+            iface.send_commands(DataFormat::U8(&data)).await?;
+        }
+        None => iface.send_commands(DataFormat::U8(&[0xB9])).await?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{block_on, command, data, init_transfers, MockInterface};
+
+    #[test]
+    fn init_128_128() {
+        assert_eq!(
+            init_transfers(Ssd1327_128_128 {}),
+            [
+                command(&[
+                    0xAE, 0xA1, 0x00, 0xA2, 0x00, 0xA4, 0xA8, 0x7F, 0xAB, 0x01, 0xB1, 0xF1, 0xB3,
+                    0x00, 0xBC, 0x08, 0xBE, 0x07, 0xD5, 0x62, 0xB6, 0x0F, 0xB9, 0x81, 0x80, 0xAF
+                ]),
+                command(&[0xA0, 0x51]),
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_area() {
+        let mut iface = MockInterface::new();
+
+        block_on(Ssd1327_128_128::draw_area(
+            &mut iface,
+            (0, 0),
+            (128, 128),
+            &[0xFF; 128 * 128 / 2],
+        ))
+        .unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                command(&[0x15, 0, 63, 0x75, 0, 127]),
+                data(&[0xFF; 128 * 128 / 2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn gray_scale_table() {
+        let mut iface = MockInterface::new();
+        let table = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+        block_on(Ssd1327_128_128::set_gray_scale_table(
+            &mut iface,
+            Some(&table),
+        ))
+        .unwrap();
+        block_on(Ssd1327_128_128::set_gray_scale_table(&mut iface, None)).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                command(&[0xB8, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]),
+                command(&[0xB9]),
+            ]
+        );
+    }
+
+    #[test]
+    fn remap_mirrors_pixels() {
+        for (rotation, remap) in [
+            (DisplayRotation::Rotate0, 0x51),
+            (DisplayRotation::Rotate90, 0x52),
+            (DisplayRotation::Rotate180, 0x42),
+            (DisplayRotation::Rotate270, 0x41),
+        ] {
+            let mut iface = MockInterface::new();
+            block_on(Ssd1327_128_128::set_rotation(&mut iface, rotation)).unwrap();
+            assert_eq!(iface.take(), [command(&[0xA0, remap])]);
+
+            // Nibble re-map is the inverse of the column address re-map
+            assert_eq!(remap & 0x02 != 0, remap & 0x01 == 0);
+        }
+    }
+}
@@ -0,0 +1,748 @@
+//! Host side model of a monochrome controller's display RAM
+//!
+//! [`Emulator`] implements `AsyncWriteOnlyDataCommand` and interprets the command stream this
+//! crate sends: page and column addressing, SSD130x addressing modes and windows, segment remap,
+//! COM scan direction, start line, display offset, invert, all on and display on/off. Data is
+//! written into a simulated GDDRAM, from which the image visible on the glass is rendered.
+//!
+//! By default the glass is assumed to be centred on the segment outputs, see
+//! [`Emulator::with_segment_offset`] for panels that aren't. It is mounted so that a remapped
+//! segment order and a reversed COM scan direction show display RAM upright. This is how the
+//! common modules are built and what `DisplayRotation::Rotate0` expects.
+//!
+//! Clones share the same state, so a clone can be kept to inspect what a driver that owns the
+//! interface has drawn:
+//!
+//! ```rust,ignore
+//! use oled_async::emulator::{Controller, Emulator};
+//!
+//! let emulator = Emulator::new(Controller::Sh1106, 128, 64);
+//! let mut display: GraphicsMode<_, _> = Builder::new(Sh1106_128_64 {})
+//!     .connect(emulator.clone())
+//!     .into();
+//!
+//! display.init(&mut delay).await.unwrap();
+//! display.set_pixel(0, 0, 1);
+//! display.flush().await.unwrap();
+//!
+//! assert!(emulator.pixel(0, 0));
+//! std::fs::write("display.png", emulator.to_png()).unwrap();
+//! ```
+
+extern crate std;
+
+use std::{cell::RefCell, rc::Rc, vec, vec::Vec};
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+
+use crate::interface::WriteCommandsAndData;
+
+/// Controller whose command set and display RAM are emulated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    /// SH1106, 132 x 64 RAM
+    Sh1106,
+    /// SH1107, 128 x 128 RAM
+    Sh1107,
+    /// SH1108, 160 x 160 RAM with two byte page addressing
+    Sh1108,
+    /// SSD1306, 128 x 64 RAM
+    Ssd1306,
+    /// SSD1309, 128 x 64 RAM
+    Ssd1309,
+}
+
+impl Controller {
+    /// Display RAM size as (columns, rows)
+    pub fn ram_size(self) -> (u16, u16) {
+        match self {
+            Controller::Sh1106 => (132, 64),
+            Controller::Sh1107 => (128, 128),
+            Controller::Sh1108 => (160, 160),
+            Controller::Ssd1306 | Controller::Ssd1309 => (128, 64),
+        }
+    }
+
+    fn is_ssd130x(self) -> bool {
+        matches!(self, Controller::Ssd1306 | Controller::Ssd1309)
+    }
+
+    /// Number of argument bytes following `opcode`
+    fn argument_count(self, opcode: u8) -> Option<usize> {
+        let count = match opcode {
+            0x00..=0x1F | 0x40..=0x7F | 0xA0 | 0xA1 | 0xA4..=0xA7 | 0xAE | 0xAF => 0,
+            0xC0..=0xCF | 0xE3 => 0,
+            0x81 | 0xA8 | 0xAD | 0xD3 | 0xD5 | 0xD9 | 0xDA | 0xDB => 1,
+            0x20 if self.is_ssd130x() => 1,
+            0x21 | 0x22 | 0xA3 if self.is_ssd130x() => 2,
+            0x20 | 0x21 => 0,
+            0x26 | 0x27 | 0x2C | 0x2D if self.is_ssd130x() => 6,
+            0x29 | 0x2A if self.is_ssd130x() => 5,
+            0x2E | 0x2F if self.is_ssd130x() => 0,
+            0x8D if self.is_ssd130x() => 1,
+            0x30..=0x33 if self == Controller::Sh1106 => 0,
+            0xDC if self == Controller::Sh1107 => 1,
+            0xA9 if self == Controller::Sh1108 => 1,
+            0xB0 if self == Controller::Sh1108 => 1,
+            0xB0..=0xB7 => 0,
+            0xB8..=0xBF if self == Controller::Sh1107 => 0,
+            _ => return None,
+        };
+
+        Some(count)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Addressing {
+    Page,
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug)]
+struct State {
+    controller: Controller,
+    width: u16,
+    height: u16,
+    segment_offset: u16,
+    ram: Vec<u8>,
+    column: u16,
+    page: u16,
+    addressing: Addressing,
+    columns: (u16, u16),
+    pages: (u16, u16),
+    segment_remap: bool,
+    reverse_com_dir: bool,
+    multiplex: u16,
+    start_line: u16,
+    display_offset: u16,
+    invert: bool,
+    all_on: bool,
+    display_on: bool,
+}
+
+impl State {
+    fn ram_columns(&self) -> u16 {
+        self.controller.ram_size().0
+    }
+
+    fn ram_pages(&self) -> u16 {
+        self.controller.ram_size().1 / 8
+    }
+
+    fn command(&mut self, opcode: u8, args: &[u8]) {
+        match opcode {
+            0x00..=0x0F => self.column = (self.column & 0xF0) | u16::from(opcode),
+            0x10..=0x1F => self.column = (self.column & 0x0F) | (u16::from(opcode & 0xF) << 4),
+            0x20 if self.controller.is_ssd130x() => {
+                self.addressing = match args[0] & 0x3 {
+                    0 => Addressing::Horizontal,
+                    1 => Addressing::Vertical,
+                    _ => Addressing::Page,
+                }
+            }
+            0x21 if self.controller.is_ssd130x() => {
+                self.columns = (u16::from(args[0]), u16::from(args[1]));
+                self.column = self.columns.0;
+            }
+            0x22 if self.controller.is_ssd130x() => {
+                self.pages = (u16::from(args[0]), u16::from(args[1]));
+                self.page = self.pages.0;
+            }
+            0x40..=0x7F => self.start_line = u16::from(opcode & 0x3F),
+            0xA0 | 0xA1 => self.segment_remap = opcode & 1 != 0,
+            0xA4 | 0xA5 => self.all_on = opcode & 1 != 0,
+            0xA6 | 0xA7 => self.invert = opcode & 1 != 0,
+            0xA8 => self.multiplex = u16::from(args[0]) + 1,
+            0xAE | 0xAF => self.display_on = opcode & 1 != 0,
+            0xB0 if self.controller == Controller::Sh1108 => self.page = u16::from(args[0]),
+            0xB0..=0xBF => self.page = u16::from(opcode & 0xF),
+            0xC0..=0xCF => self.reverse_com_dir = opcode & 0x8 != 0,
+            0xD3 => self.display_offset = u16::from(args[0]),
+            0xDC => self.start_line = u16::from(args[0]),
+            // Contrast, timing, power and scrolling don't change the RAM or its mapping
+            _ => {}
+        }
+    }
+
+    fn data(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            let column = self.column % self.ram_columns();
+            let page = self.page % self.ram_pages();
+            let index = page as usize * self.ram_columns() as usize + column as usize;
+            self.ram[index] = byte;
+            self.advance();
+        }
+    }
+
+    fn advance(&mut self) {
+        let (first_column, last_column) = self.columns;
+        let (first_page, last_page) = self.pages;
+
+        match self.addressing {
+            Addressing::Page if self.controller.is_ssd130x() => {
+                self.column = if self.column >= last_column {
+                    first_column
+                } else {
+                    self.column + 1
+                };
+            }
+            // The SH110x column address stops incrementing at the end of the RAM
+            Addressing::Page => self.column = (self.column + 1).min(self.ram_columns() - 1),
+            Addressing::Horizontal => {
+                if self.column >= last_column {
+                    self.column = first_column;
+                    self.page = if self.page >= last_page {
+                        first_page
+                    } else {
+                        self.page + 1
+                    };
+                } else {
+                    self.column += 1;
+                }
+            }
+            Addressing::Vertical => {
+                if self.page >= last_page {
+                    self.page = first_page;
+                    self.column = if self.column >= last_column {
+                        first_column
+                    } else {
+                        self.column + 1
+                    };
+                } else {
+                    self.page += 1;
+                }
+            }
+        }
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> bool {
+        if x >= u32::from(self.width) || y >= u32::from(self.height) || !self.display_on {
+            return false;
+        }
+        if self.all_on {
+            return true;
+        }
+
+        let (ram_columns, ram_rows) = self.controller.ram_size();
+
+        // Segment and COM outputs run right to left and bottom to top across the glass
+        let segment = self.segment_offset + self.width - 1 - x as u16;
+        let com = self.height - 1 - y as u16;
+
+        let column = if self.segment_remap {
+            ram_columns - 1 - segment
+        } else {
+            segment
+        };
+        let line = if self.reverse_com_dir {
+            match self.multiplex.checked_sub(com + 1) {
+                Some(line) => line,
+                None => return false,
+            }
+        } else if com < self.multiplex {
+            com
+        } else {
+            return false;
+        };
+        let row = (line + self.start_line + self.display_offset) % ram_rows;
+
+        let byte = self.ram[(row / 8) as usize * ram_columns as usize + column as usize];
+
+        (byte & (1 << (row % 8)) != 0) ^ self.invert
+    }
+}
+
+/// Emulated controller with a monochrome panel attached. See the [module level
+/// documentation](crate::emulator) for details.
+#[derive(Debug, Clone)]
+pub struct Emulator {
+    state: Rc<RefCell<State>>,
+}
+
+impl Emulator {
+    /// Create an emulator for a `width` x `height` panel driven by `controller`. The RAM content
+    /// is all zero and the registers are at their reset values.
+    pub fn new(controller: Controller, width: u16, height: u16) -> Self {
+        let (ram_columns, ram_rows) = controller.ram_size();
+        assert!(
+            width <= ram_columns && height <= ram_rows,
+            "panel is larger than the display RAM"
+        );
+
+        let state = State {
+            controller,
+            width,
+            height,
+            segment_offset: (ram_columns - width) / 2,
+            ram: vec![0; ram_columns as usize * ram_rows as usize / 8],
+            column: 0,
+            page: 0,
+            addressing: Addressing::Page,
+            columns: (0, ram_columns - 1),
+            pages: (0, ram_rows / 8 - 1),
+            segment_remap: false,
+            reverse_com_dir: false,
+            multiplex: ram_rows,
+            start_line: 0,
+            display_offset: 0,
+            invert: false,
+            all_on: false,
+            display_on: false,
+        };
+
+        Emulator {
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    /// Connect the glass to the segment outputs starting at `offset` instead of centring it
+    pub fn with_segment_offset(self, offset: u16) -> Self {
+        {
+            let mut state = self.state.borrow_mut();
+            assert!(
+                offset + state.width <= state.controller.ram_size().0,
+                "panel extends past the last segment output"
+            );
+            state.segment_offset = offset;
+        }
+
+        self
+    }
+
+    /// Panel size as (width, height)
+    pub fn size(&self) -> (u32, u32) {
+        let state = self.state.borrow();
+        (state.width.into(), state.height.into())
+    }
+
+    /// Whether the pixel at (`x`, `y`) of the panel is lit. Pixels outside the panel are off.
+    pub fn pixel(&self, x: u32, y: u32) -> bool {
+        self.state.borrow().pixel(x, y)
+    }
+
+    /// Whether the display is switched on
+    pub fn is_on(&self) -> bool {
+        self.state.borrow().display_on
+    }
+
+    /// The raw display RAM, one byte per column of 8 rows, page by page
+    pub fn ram(&self) -> Vec<u8> {
+        self.state.borrow().ram.clone()
+    }
+
+    /// Render the visible image as a binary PBM (P4) file. Lit pixels are black.
+    pub fn to_pbm(&self) -> Vec<u8> {
+        let (width, height) = self.size();
+        let mut file = std::format!("P4\n{} {}\n", width, height).into_bytes();
+
+        for y in 0..height {
+            file.extend(self.packed_row(y, true));
+        }
+
+        file
+    }
+
+    /// Render the visible image as a 1 bit grayscale PNG file. Lit pixels are white.
+    pub fn to_png(&self) -> Vec<u8> {
+        let (width, height) = self.size();
+
+        let mut header = Vec::new();
+        header.extend(width.to_be_bytes());
+        header.extend(height.to_be_bytes());
+        // Bit depth 1, grayscale, deflate, adaptive filtering, no interlace
+        header.extend([1, 0, 0, 0, 0]);
+
+        let mut scanlines = Vec::new();
+        for y in 0..height {
+            // Filter type none
+            scanlines.push(0);
+            scanlines.extend(self.packed_row(y, false));
+        }
+
+        let mut file = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+        png_chunk(&mut file, b"IHDR", &header);
+        png_chunk(&mut file, b"IDAT", &zlib_stored(&scanlines));
+        png_chunk(&mut file, b"IEND", &[]);
+
+        file
+    }
+
+    fn packed_row(&self, y: u32, lit_bit: bool) -> Vec<u8> {
+        let (width, _) = self.size();
+        let state = self.state.borrow();
+        let mut row = vec![0u8; width.div_ceil(8) as usize];
+
+        for x in 0..width {
+            if state.pixel(x, y) == lit_bit {
+                row[(x / 8) as usize] |= 0x80 >> (x % 8);
+            }
+        }
+
+        row
+    }
+
+    fn commands(&self, bytes: &[u8]) -> Result<(), DisplayError> {
+        let mut state = self.state.borrow_mut();
+        let mut bytes = bytes;
+
+        while let Some((&opcode, rest)) = bytes.split_first() {
+            let count = state
+                .controller
+                .argument_count(opcode)
+                .ok_or(DisplayError::InvalidFormatError)?;
+            if rest.len() < count {
+                return Err(DisplayError::InvalidFormatError);
+            }
+
+            state.command(opcode, &rest[..count]);
+            bytes = &rest[count..];
+        }
+
+        Ok(())
+    }
+
+    fn bytes(format: DataFormat<'_>) -> Result<Vec<u8>, DisplayError> {
+        match format {
+            DataFormat::U8(bytes) => Ok(bytes.to_vec()),
+            DataFormat::U8Iter(iter) => Ok(iter.collect()),
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+}
+
+impl WriteCommandsAndData for Emulator {}
+
+impl AsyncWriteOnlyDataCommand for Emulator {
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.commands(&Self::bytes(cmd)?)
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.state.borrow_mut().data(&Self::bytes(buf)?);
+        Ok(())
+    }
+}
+
+fn png_chunk(file: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    file.extend((data.len() as u32).to_be_bytes());
+    let start = file.len();
+    file.extend(kind);
+    file.extend(data);
+    let crc = crc32(&file[start..]);
+    file.extend(crc.to_be_bytes());
+}
+
+/// Wrap `data` in a zlib stream of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+
+    if blocks.peek().is_none() {
+        stream.extend([1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        stream.push(blocks.peek().is_none() as u8);
+        stream.extend(len.to_le_bytes());
+        stream.extend((!len).to_le_bytes());
+        stream.extend(block);
+    }
+
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % 65521;
+        (a, (b + a) % 65521)
+    });
+    stream.extend(((b << 16) | a).to_be_bytes());
+
+    stream
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::DisplayConfig,
+        display::DisplayVariant,
+        displayrotation::DisplayRotation,
+        displays::{
+            any::AnyDisplay,
+            sh1106::Sh1106_128_64,
+            sh1107::{Sh1107, Sh1107_128_128, Sh1107_64_128},
+            sh1108::{Sh1108_128_160, Sh1108_160_160, Sh1108_64_160, Sh1108_96_160},
+            ssd1306::{
+                Ssd1306_128_32, Ssd1306_128_64, Ssd1306_64_48, Ssd1306_72_40, Ssd1306_96_16,
+            },
+            ssd1309::{Ssd1309, Ssd1309_128_64},
+        },
+        mode::{displaymode::DisplayModeTrait, GraphicsMode},
+        properties::DisplayProperties,
+        test_helpers::{block_on, MockInterface},
+    };
+
+    fn emulator<DV: DisplayVariant>(controller: Controller) -> Emulator {
+        Emulator::new(controller, DV::WIDTH, DV::HEIGHT)
+    }
+
+    /// Draw an asymmetric pattern in every rotation and check it appears rotated on the glass
+    fn check_variant<DV: DisplayVariant + Copy>(variant: DV, emulator: impl Fn() -> Emulator) {
+        let (width, height) = variant.size();
+        let (width, height) = (u32::from(width), u32::from(height));
+
+        for rotation in [
+            DisplayRotation::Rotate0,
+            DisplayRotation::Rotate90,
+            DisplayRotation::Rotate180,
+            DisplayRotation::Rotate270,
+        ] {
+            let emulator = emulator();
+            let mut display: GraphicsMode<DV, Emulator> =
+                GraphicsMode::new(DisplayProperties::new(
+                    variant,
+                    emulator.clone(),
+                    rotation,
+                    DisplayConfig::default(),
+                ));
+            block_on(display.init(&mut MockInterface::new())).unwrap();
+
+            let (logical_width, logical_height) = display.get_dimensions();
+            let (logical_width, logical_height) =
+                (u32::from(logical_width), u32::from(logical_height));
+            let points = [
+                (0, 0),
+                (1, 0),
+                (0, 2),
+                (logical_width - 1, 0),
+                (0, logical_height - 1),
+                (logical_width - 1, logical_height - 1),
+            ];
+            for &(x, y) in &points {
+                display.set_pixel(x, y, 1);
+            }
+            block_on(display.flush()).unwrap();
+
+            // Where logical (x, y) shows up on the glass
+            let glass = |x: u32, y: u32| match rotation {
+                DisplayRotation::Rotate0 => (x, y),
+                DisplayRotation::Rotate90 => (width - 1 - y, x),
+                DisplayRotation::Rotate180 => (width - 1 - x, height - 1 - y),
+                DisplayRotation::Rotate270 => (y, height - 1 - x),
+            };
+            let expected: Vec<_> = points.iter().map(|&(x, y)| glass(x, y)).collect();
+
+            for y in 0..height {
+                for x in 0..width {
+                    assert_eq!(
+                        emulator.pixel(x, y),
+                        expected.contains(&(x, y)),
+                        "pixel ({}, {}) of {}x{} panel in {:?}",
+                        x,
+                        y,
+                        width,
+                        height,
+                        rotation
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sh1106() {
+        check_variant(Sh1106_128_64 {}, || {
+            emulator::<Sh1106_128_64>(Controller::Sh1106)
+        });
+    }
+
+    #[test]
+    fn sh1107() {
+        check_variant(Sh1107_64_128 {}, || {
+            emulator::<Sh1107_64_128>(Controller::Sh1107)
+        });
+        check_variant(Sh1107_128_128 {}, || {
+            emulator::<Sh1107_128_128>(Controller::Sh1107)
+        });
+        // Off centre: RAM columns 16 to 79 when remapped, so segments 48 to 111
+        check_variant(Sh1107::<64, 128, 16> {}, || {
+            emulator::<Sh1107<64, 128, 16>>(Controller::Sh1107).with_segment_offset(48)
+        });
+    }
+
+    #[test]
+    fn sh1108() {
+        check_variant(Sh1108_64_160 {}, || {
+            emulator::<Sh1108_64_160>(Controller::Sh1108)
+        });
+        check_variant(Sh1108_96_160 {}, || {
+            emulator::<Sh1108_96_160>(Controller::Sh1108)
+        });
+        check_variant(Sh1108_128_160 {}, || {
+            emulator::<Sh1108_128_160>(Controller::Sh1108)
+        });
+        check_variant(Sh1108_160_160 {}, || {
+            emulator::<Sh1108_160_160>(Controller::Sh1108)
+        });
+    }
+
+    #[test]
+    fn ssd1306() {
+        check_variant(Ssd1306_128_64 {}, || {
+            emulator::<Ssd1306_128_64>(Controller::Ssd1306)
+        });
+        check_variant(Ssd1306_128_32 {}, || {
+            emulator::<Ssd1306_128_32>(Controller::Ssd1306)
+        });
+        check_variant(Ssd1306_96_16 {}, || {
+            emulator::<Ssd1306_96_16>(Controller::Ssd1306).with_segment_offset(32)
+        });
+        check_variant(Ssd1306_72_40 {}, || {
+            emulator::<Ssd1306_72_40>(Controller::Ssd1306)
+        });
+        check_variant(Ssd1306_64_48 {}, || {
+            emulator::<Ssd1306_64_48>(Controller::Ssd1306)
+        });
+    }
+
+    #[test]
+    fn ssd1309() {
+        check_variant(Ssd1309_128_64 {}, || {
+            emulator::<Ssd1309_128_64>(Controller::Ssd1309)
+        });
+        // Off centre: RAM columns 8 to 103 when remapped, so segments 24 to 119
+        check_variant(Ssd1309::<96, 64, 8> {}, || {
+            emulator::<Ssd1309<96, 64, 8>>(Controller::Ssd1309).with_segment_offset(24)
+        });
+    }
+
+    #[test]
+    fn any_display() {
+        check_variant(AnyDisplay::<128, 160>::new(Sh1107_128_128 {}), || {
+            emulator::<Sh1107_128_128>(Controller::Sh1107)
+        });
+        check_variant(AnyDisplay::<128, 160>::new(Sh1108_128_160 {}), || {
+            emulator::<Sh1108_128_160>(Controller::Sh1108)
+        });
+        check_variant(AnyDisplay::<128, 160>::new(Ssd1306_128_64 {}), || {
+            emulator::<Ssd1306_128_64>(Controller::Ssd1306)
+        });
+    }
+
+    /// Draw the same frames through `flush` and `flush_combined` and compare the display RAM
+    fn check_flush_combined<DV: DisplayVariant + Copy>(variant: DV, controller: Controller) {
+        let emulators = [emulator::<DV>(controller), emulator::<DV>(controller)];
+        let mut displays = emulators.clone().map(|emulator| {
+            let mut display: GraphicsMode<DV, Emulator> =
+                GraphicsMode::new(DisplayProperties::new(
+                    variant,
+                    emulator,
+                    DisplayRotation::Rotate0,
+                    DisplayConfig::default(),
+                ));
+            block_on(display.init(&mut MockInterface::new())).unwrap();
+            display
+        });
+
+        for (x, y) in [(0, 0), (5, 9), (DV::WIDTH - 1, DV::HEIGHT - 1)] {
+            for display in &mut displays {
+                display.set_pixel(u32::from(x), u32::from(y), 1);
+            }
+            let [flushed, combined] = &mut displays;
+            block_on(flushed.flush()).unwrap();
+            block_on(combined.flush_combined()).unwrap();
+
+            assert_eq!(emulators[0].ram(), emulators[1].ram());
+        }
+    }
+
+    #[test]
+    fn flush_combined() {
+        check_flush_combined(Sh1106_128_64 {}, Controller::Sh1106);
+        check_flush_combined(Sh1107_128_128 {}, Controller::Sh1107);
+        check_flush_combined(Ssd1306_128_32 {}, Controller::Ssd1306);
+    }
+
+    #[test]
+    fn display_state() {
+        let mut emulator = Emulator::new(Controller::Ssd1306, 128, 64);
+
+        block_on(emulator.send_commands(DataFormat::U8(&[0xA1, 0xC8, 0xB0, 0x00, 0x10]))).unwrap();
+        block_on(emulator.send_data(DataFormat::U8(&[0x01]))).unwrap();
+        assert!(!emulator.pixel(0, 0), "display is off after reset");
+
+        block_on(emulator.send_commands(DataFormat::U8(&[0xAF]))).unwrap();
+        assert!(emulator.pixel(0, 0));
+        assert!(!emulator.pixel(1, 0));
+
+        block_on(emulator.send_commands(DataFormat::U8(&[0xA7]))).unwrap();
+        assert!(!emulator.pixel(0, 0));
+        assert!(emulator.pixel(1, 0));
+
+        block_on(emulator.send_commands(DataFormat::U8(&[0xA6, 0xA5]))).unwrap();
+        assert!(emulator.pixel(1, 0));
+
+        // Start line and display offset both move RAM row 1 to the top of the glass
+        block_on(emulator.send_commands(DataFormat::U8(&[0xA4, 0x41]))).unwrap();
+        block_on(emulator.send_data(DataFormat::U8(&[0x02]))).unwrap();
+        assert!(emulator.pixel(1, 0));
+        block_on(emulator.send_commands(DataFormat::U8(&[0x40, 0xD3, 0x01]))).unwrap();
+        assert!(emulator.pixel(1, 0));
+        assert!(!emulator.pixel(0, 0));
+        assert!(emulator.pixel(0, 63));
+    }
+
+    #[test]
+    fn unknown_command() {
+        let mut emulator = Emulator::new(Controller::Sh1106, 128, 64);
+
+        assert!(matches!(
+            block_on(emulator.send_commands(DataFormat::U8(&[0x8D, 0x14]))),
+            Err(DisplayError::InvalidFormatError)
+        ));
+        assert!(matches!(
+            block_on(emulator.send_commands(DataFormat::U8(&[0x81]))),
+            Err(DisplayError::InvalidFormatError)
+        ));
+    }
+
+    #[test]
+    fn image_files() {
+        let mut emulator = Emulator::new(Controller::Ssd1306, 72, 40);
+
+        block_on(emulator.send_commands(DataFormat::U8(&[
+            0xAF, 0xA8, 39, 0xA1, 0xC8, 0xB0, 0x0C, 0x11,
+        ])))
+        .unwrap();
+        block_on(emulator.send_data(DataFormat::U8(&[0x01]))).unwrap();
+
+        let pbm = emulator.to_pbm();
+        assert!(pbm.starts_with(b"P4\n72 40\n"));
+        assert_eq!(pbm.len(), 9 + 9 * 40);
+        assert_eq!(pbm[9], 0x80);
+        assert_eq!(pbm[10..], [0; 9 * 40 - 1]);
+
+        let png = emulator.to_png();
+        assert_eq!(
+            png[..8],
+            [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n']
+        );
+        assert_eq!(png[12..16], *b"IHDR");
+        assert_eq!(png[16..24], [0, 0, 0, 72, 0, 0, 0, 40]);
+        assert_eq!(
+            png[png.len() - 12..],
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]
+        );
+    }
+}
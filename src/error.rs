@@ -0,0 +1,34 @@
+//! Errors returned by the driver
+
+use display_interface::DisplayError;
+use hal::digital::ErrorKind;
+
+/// Errors in this crate
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// Communication with the display through the display interface failed
+    Comm(DisplayError),
+    /// Setting the reset pin or a power enable pin failed
+    Pin(ErrorKind),
+    /// A [`DisplayConfig`](crate::DisplayConfig) setting is out of range for the controller
+    InvalidConfig,
+    /// A draw area lies outside the display, or the data does not fit it
+    InvalidDrawArea,
+    /// The framebuffer is smaller than the display variant needs
+    BufferTooSmall,
+    /// The display variant does not support the requested command
+    Unsupported,
+}
+
+impl Error {
+    /// Wrap the error of a reset or power enable pin
+    pub(crate) fn pin<E: hal::digital::Error>(error: E) -> Self {
+        Error::Pin(error.kind())
+    }
+}
+
+impl From<DisplayError> for Error {
+    fn from(error: DisplayError) -> Self {
+        Error::Comm(error)
+    }
+}
@@ -0,0 +1,214 @@
+//! Data-driven init sequences
+//!
+//! A display variant can describe its initialisation as a `const` table of [`InitStep`]s instead
+//! of implementing [`DisplayVariant::init_column_mode`](crate::display::DisplayVariant) by hand.
+//! Steps that depend on the [`DisplayConfig`] or on the display size are resolved when the
+//! sequence is sent.
+//!
+//! ```rust,ignore
+//! use oled_async::{
+//!     display::DisplayVariant,
+//!     init::{Command, InitStep},
+//! };
+//!
+//! pub struct MyPanel_128_64 {}
+//!
+//! impl DisplayVariant for MyPanel_128_64 {
+//!     const WIDTH: u16 = 128;
+//!     const HEIGHT: u16 = 64;
+//!     const INIT_SEQUENCE: &'static [InitStep] = &[
+//!         InitStep::Command(Command::DisplayOn(false)),
+//!         InitStep::Clock(0x8, 0x0),
+//!         InitStep::Multiplex,
+//!         InitStep::ChargePump,
+//!         InitStep::DelayMs(100),
+//!         InitStep::Contrast(0x80),
+//!         InitStep::Command(Command::DisplayOn(true)),
+//!     ];
+//! }
+//! ```
+
+use display_interface::AsyncWriteOnlyDataCommand;
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{
+    command::{CommandBatch, VcomhLevel},
+    config::DisplayConfig,
+    Error,
+};
+
+pub use crate::command::Command;
+
+/// A single step of an init sequence
+#[derive(Debug, Clone, Copy)]
+pub enum InitStep {
+    /// Send a fixed command
+    Command(Command),
+    /// Set the contrast from the config, or the given default
+    Contrast(u8),
+    /// Set the oscillator frequency and clock divide ratio from the config, or the given default
+    Clock(u8, u8),
+    /// Set the pre-charge periods from the config, or the given default
+    PreCharge(u8, u8),
+    /// Set the VCOMH deselect level from the config, or the given default
+    Vcomh(VcomhLevel),
+    /// Turn the SH110x DC-DC converter (0xAD) on, unless the config has an external VCC
+    ChargePump,
+    /// Turn the SSD130x charge pump (0x8D) on, unless the config has an external VCC
+    SsdChargePump,
+    /// Set the multiplex ratio to the display height
+    Multiplex,
+    /// Wait for the given number of milliseconds
+    DelayMs(u32),
+}
+
+/// Send an init sequence for a display of the given dimensions
+pub async fn send_init_sequence<DI, D>(
+    iface: &mut DI,
+    delay: &mut D,
+    sequence: &[InitStep],
+    config: &DisplayConfig,
+    dimensions: (u16, u16),
+) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    D: DelayNs,
+{
+    let (_, display_height) = dimensions;
+    let mut batch = CommandBatch::<64>::new();
+
+    for step in sequence {
+        let command = match *step {
+            InitStep::Command(command) => command,
+            InitStep::Contrast(contrast) => Command::Contrast(config.contrast.unwrap_or(contrast)),
+            InitStep::Clock(fosc, div) => {
+                let (fosc, div) = config.clock.unwrap_or((fosc, div));
+                Command::DisplayClockDiv(fosc, div)
+            }
+            InitStep::PreCharge(phase1, phase2) => {
+                let (phase1, phase2) = config.precharge.unwrap_or((phase1, phase2));
+                Command::PreChargePeriod(phase1, phase2)
+            }
+            InitStep::Vcomh(level) => Command::VcomhDeselect(config.vcomh.unwrap_or(level)),
+            InitStep::ChargePump => Command::ChargePump(!config.external_vcc),
+            InitStep::SsdChargePump => Command::SsdChargePump(!config.external_vcc),
+            InitStep::Multiplex => Command::Multiplex((display_height - 1) as u8),
+            InitStep::DelayMs(ms) => {
+                batch.send(iface).await?;
+                delay.delay_ms(ms).await;
+                continue;
+            }
+        };
+
+        batch.queue(iface, command).await?;
+    }
+
+    batch.send(iface).await
+}
+
+/// Command switching the charge pump or DC-DC converter turned on by `sequence`, if it has one
+pub fn charge_pump_command(sequence: &[InitStep], on: bool) -> Option<Command> {
+    sequence.iter().find_map(|step| match step {
+        InitStep::ChargePump => Some(Command::ChargePump(on)),
+        InitStep::SsdChargePump => Some(Command::SsdChargePump(on)),
+        _ => None,
+    })
+}
+
+/// Join two init sequences in a `const` context, e.g. a controller's common steps and the steps
+/// specific to one panel. `N` must be the combined length.
+pub const fn concat<const A: usize, const B: usize, const N: usize>(
+    first: [InitStep; A],
+    second: [InitStep; B],
+) -> [InitStep; N] {
+    assert!(
+        A + B == N,
+        "length of the joined init sequence must be A + B"
+    );
+
+    let mut sequence = [InitStep::Command(Command::Noop); N];
+    let mut i = 0;
+    while i < A {
+        sequence[i] = first[i];
+        i += 1;
+    }
+    while i < N {
+        sequence[i] = second[i - A];
+        i += 1;
+    }
+
+    sequence
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::test_helpers::{block_on, commands, MockInterface, Transfer};
+
+    const SEQUENCE: [InitStep; 9] = concat::<7, 2, 9>(
+        [
+            InitStep::Clock(0x8, 0x0),
+            InitStep::Multiplex,
+            InitStep::ChargePump,
+            InitStep::SsdChargePump,
+            InitStep::DelayMs(10),
+            InitStep::Contrast(0x80),
+            InitStep::PreCharge(0x1, 0xF),
+        ],
+        [
+            InitStep::Vcomh(VcomhLevel::Auto),
+            InitStep::Command(Command::DisplayOn(true)),
+        ],
+    );
+
+    fn send(config: &DisplayConfig) -> Vec<Transfer> {
+        let mut iface = MockInterface::new();
+        let mut delay = iface.clone();
+
+        block_on(send_init_sequence(
+            &mut iface,
+            &mut delay,
+            &SEQUENCE,
+            config,
+            (128, 32),
+        ))
+        .unwrap();
+        iface.take()
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(
+            send(&DisplayConfig::default()),
+            [
+                commands([&[0xD5, 0x80], &[0xA8, 31], &[0xAD, 0x8B], &[0x8D, 0x14]]),
+                Transfer::Delay(10_000_000),
+                commands([&[0x81, 0x80], &[0xD9, 0xF1], &[0xDB, 0x40], &[0xAF]]),
+            ]
+        );
+    }
+
+    #[test]
+    fn config() {
+        let config = DisplayConfig {
+            contrast: Some(0x10),
+            clock: Some((0xF, 0x2)),
+            precharge: Some((0x2, 0x2)),
+            vcomh: Some(VcomhLevel::V077),
+            external_vcc: true,
+        };
+
+        assert_eq!(
+            send(&config),
+            [
+                commands([&[0xD5, 0xF2], &[0xA8, 31], &[0xAD, 0x8A], &[0x8D, 0x10]]),
+                Transfer::Delay(10_000_000),
+                commands([&[0x81, 0x10], &[0xD9, 0x22], &[0xDB, 0x20], &[0xAF]]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! I2C interface for SH110x and SSD130x controllers
+//!
+//! Every I2C write starts with a control byte. Its D/C# bit selects whether the following bytes
+//! are commands or display data, and its Co bit says whether another control byte follows after
+//! the next byte. [`I2cInterface`] uses the Co bit to send the address commands of a page and
+//! its data in a single write, where a generic interface needs one write for each.
+//!
+//! ```rust,ignore
+//! use oled_async::interface::I2cInterface;
+//!
+//! let mut display: GraphicsMode<_, _, [u8; Sh1107_128_128::BUFFER_SIZE]> =
+//!     Builder::new(Sh1107_128_128 {})
+//!         .connect(I2cInterface::new(i2c, 0x3C))
+//!         .into();
+//!
+//! display.init(&mut delay).await.unwrap();
+//! display.flush_combined().await.unwrap();
+//! ```
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+use embedded_hal_async::i2c::{I2c, Operation};
+
+use super::WriteCommandsAndData;
+
+/// Control byte before a stream of commands
+const COMMANDS: u8 = 0x00;
+/// Control byte before a stream of display data
+const DATA: u8 = 0x40;
+/// Control byte before a single command byte that is followed by another control byte
+const CONTINUED_COMMAND: u8 = 0x80;
+
+/// Command bytes that `send_commands_and_data` sends in the same write as the data. Longer
+/// command sequences are sent in a write of their own.
+const MAX_COMBINED_COMMANDS: usize = 8;
+
+/// Bytes of an iterator written at a time
+const CHUNK_SIZE: usize = 16;
+
+/// Display interface over an `embedded_hal_async` I2C bus
+#[derive(Debug)]
+pub struct I2cInterface<I2C> {
+    i2c: I2C,
+    address: u8,
+}
+
+impl<I2C: I2c> I2cInterface<I2C> {
+    /// Create an interface for the display at the 7-bit `address`, usually `0x3C` or `0x3D`
+    pub fn new(i2c: I2C, address: u8) -> Self {
+        Self { i2c, address }
+    }
+
+    /// Release the I2C bus
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    async fn write(&mut self, control: u8, format: DataFormat<'_>) -> Result<(), DisplayError> {
+        match format {
+            DataFormat::U8(bytes) => self
+                .i2c
+                .transaction(
+                    self.address,
+                    &mut [Operation::Write(&[control]), Operation::Write(bytes)],
+                )
+                .await
+                .map_err(|_| DisplayError::BusWriteError),
+            DataFormat::U8Iter(iter) => {
+                let mut chunk = [control; CHUNK_SIZE + 1];
+                loop {
+                    let mut len = 0;
+                    for (slot, byte) in chunk[1..].iter_mut().zip(&mut *iter) {
+                        *slot = byte;
+                        len += 1;
+                    }
+                    if len == 0 {
+                        return Ok(());
+                    }
+
+                    self.i2c
+                        .write(self.address, &chunk[..=len])
+                        .await
+                        .map_err(|_| DisplayError::BusWriteError)?;
+                }
+            }
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+}
+
+impl<I2C: I2c> AsyncWriteOnlyDataCommand for I2cInterface<I2C> {
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.write(COMMANDS, cmd).await
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.write(DATA, buf).await
+    }
+}
+
+impl<I2C: I2c> WriteCommandsAndData for I2cInterface<I2C> {
+    async fn send_commands_and_data(
+        &mut self,
+        commands: &[u8],
+        data: &[u8],
+    ) -> Result<(), DisplayError> {
+        if commands.len() > MAX_COMBINED_COMMANDS {
+            self.send_commands(DataFormat::U8(commands)).await?;
+            return self.send_data(DataFormat::U8(data)).await;
+        }
+
+        // Each command byte gets a control byte with Co set, the data follows the last control
+        // byte with Co cleared
+        let mut framed = [CONTINUED_COMMAND; 2 * MAX_COMBINED_COMMANDS];
+        for (pair, &command) in framed.chunks_exact_mut(2).zip(commands) {
+            pair[1] = command;
+        }
+
+        self.i2c
+            .transaction(
+                self.address,
+                &mut [
+                    Operation::Write(&framed[..2 * commands.len()]),
+                    Operation::Write(&[DATA]),
+                    Operation::Write(data),
+                ],
+            )
+            .await
+            .map_err(|_| DisplayError::BusWriteError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{vec, vec::Vec};
+
+    use super::*;
+    use crate::test_helpers::block_on;
+
+    /// I2C bus recording the address and the bytes written by each transaction
+    #[derive(Default)]
+    struct MockI2c {
+        writes: Vec<(u8, Vec<u8>)>,
+    }
+
+    impl embedded_hal_async::i2c::ErrorType for MockI2c {
+        type Error = core::convert::Infallible;
+    }
+
+    impl I2c for MockI2c {
+        async fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            let mut bytes = Vec::new();
+            for operation in operations {
+                if let Operation::Write(write) = operation {
+                    bytes.extend_from_slice(write);
+                }
+            }
+            self.writes.push((address, bytes));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn commands_and_data() {
+        let mut iface = I2cInterface::new(MockI2c::default(), 0x3C);
+
+        block_on(iface.send_commands(DataFormat::U8(&[0xAE, 0xD5, 0x80]))).unwrap();
+        block_on(iface.send_data(DataFormat::U8(&[1, 2, 3]))).unwrap();
+        block_on(iface.send_data(DataFormat::U8Iter(&mut (0..20u8)))).unwrap();
+
+        assert_eq!(
+            iface.release().writes,
+            [
+                (0x3C, vec![0x00, 0xAE, 0xD5, 0x80]),
+                (0x3C, vec![0x40, 1, 2, 3]),
+                (0x3C, [&[0x40][..], &(0..16).collect::<Vec<u8>>()].concat()),
+                (0x3C, vec![0x40, 16, 17, 18, 19]),
+            ]
+        );
+    }
+
+    #[test]
+    fn combined() {
+        let mut iface = I2cInterface::new(MockI2c::default(), 0x3D);
+
+        block_on(iface.send_commands_and_data(&[0xB2, 0x02, 0x10], &[0xAA, 0x55])).unwrap();
+        // Too many commands for one write
+        block_on(iface.send_commands_and_data(&[0xE3; 9], &[0xFF])).unwrap();
+
+        assert_eq!(
+            iface.release().writes,
+            [
+                (
+                    0x3D,
+                    vec![0x80, 0xB2, 0x80, 0x02, 0x80, 0x10, 0x40, 0xAA, 0x55]
+                ),
+                (0x3D, [&[0x00][..], &[0xE3; 9]].concat()),
+                (0x3D, vec![0x40, 0xFF]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Display interfaces
+//!
+//! Any `display_interface::AsyncWriteOnlyDataCommand` drives the display. Interfaces that also
+//! implement [`WriteCommandsAndData`] can address and fill a page in one bus transfer, which
+//! [`DisplayProperties::draw_combined`](crate::properties::DisplayProperties::draw_combined) and
+//! [`GraphicsMode::flush_combined`](crate::mode::GraphicsMode::flush_combined) make use of.
+
+pub mod i2c;
+
+pub use self::i2c::I2cInterface;
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+
+/// Display interface that can send a few commands followed by data in a single bus transfer
+///
+/// Implemented by [`I2cInterface`] and the emulator. The interfaces of `display-interface-spi`
+/// and `display-interface-i2c` belong to other crates, so it can't be implemented for them
+/// outside of this crate. Wrap those in a local newtype to use the combined methods.
+pub trait WriteCommandsAndData: AsyncWriteOnlyDataCommand {
+    /// Send `commands`, then `data`. The default sends them as two separate transfers, so an
+    /// interface of the implementing crate can opt in with an empty `impl`.
+    #[allow(async_fn_in_trait)]
+    async fn send_commands_and_data(
+        &mut self,
+        commands: &[u8],
+        data: &[u8],
+    ) -> Result<(), DisplayError> {
+        self.send_commands(DataFormat::U8(commands)).await?;
+        self.send_data(DataFormat::U8(data)).await
+    }
+}
@@ -14,27 +14,49 @@
 //! this:
 //!
 //! ```rust,no_run
+//! # use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+//! # struct Interface;
+//! # impl AsyncWriteOnlyDataCommand for Interface {
+//! #     async fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+//! #         Ok(())
+//! #     }
+//! #     async fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! # struct Delay;
+//! # impl embedded_hal_async::delay::DelayNs for Delay {
+//! #     async fn delay_ns(&mut self, _: u32) {}
+//! # }
+//! # struct Pin;
+//! # impl embedded_hal::digital::ErrorType for Pin {
+//! #     type Error = core::convert::Infallible;
+//! # }
+//! # impl embedded_hal::digital::OutputPin for Pin {
+//! #     fn set_low(&mut self) -> Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! #     fn set_high(&mut self) -> Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! # async fn example(display_interface: Interface, reset: Pin, mut delay: Delay) {
 //! use oled_async::{prelude::*, Builder};
 //! use oled_async::displays::sh1107::Sh1107_128_128;
 //!
 //! let raw_disp = Builder::new(Sh1107_128_128 {})
-//!     .with_rotation(crate::DisplayRotation::Rotate180)
+//!     .with_rotation(DisplayRotation::Rotate180)
+//!     .with_reset_pin(reset)
 //!     .connect(display_interface);
-//! let mut display: GraphicsMode<_, _> = raw_disp.into();
-//! display.reset(&mut reset, &mut delay).unwrap();
-//! display.init().await.unwrap();
+//! let mut display: GraphicsMode<_, _, [u8; 128 * 128 / 8], _> = raw_disp.into();
+//! display.reset_and_init(&mut delay).await.unwrap();
 //! display.clear();
 //! display.flush().await.unwrap();
 //!
-//!
-//! let mut display: GraphicsMode<_> = Builder::new().connect_i2c(i2c).into();
-//!
-//! display.init().unwrap();
-//! display.flush().unwrap();
-//!
 //! display.set_pixel(10, 20, 1);
 //!
 //! display.flush().await.unwrap();
+//! # }
 //! ```
 //!
 //! See the [examples](https://github.com/cschuhen/oled_drivers/tree/master/examples)
@@ -44,47 +66,68 @@
 //! It's possible to customise the driver to suit your display/application. Take a look at the
 //! [Builder] for available options. Look in src/variants for different supported display
 //! variants.
-//!
-//! # Examples
-//!
-//! ## Draw some text to the display
-//!
-//! Uses [mode::GraphicsMode] and [embedded_graphics](../embedded_graphics/index.html).
-//!
-//! ```rust,no_run
-//! use embedded_graphics::{
-//!     mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-//!     pixelcolor::BinaryColor,
-//!     prelude::*,
-//!     text::{Baseline, Text},
-//! };
-//! use oled_async::{prelude::*, Builder};
-//! # let display_interface # See display_interface crate or examples
-//!
-//! let mut display: GraphicsMode<_, _> = Builder::new(oled_async::displays::sh1107::Sh1107_128_128 {})
-//!         .with_rotation(crate::DisplayRotation::Rotate180)
-//!         .connect(display_interface)
-//!         .into();
-//!
-//! display.init().unwrap();
-//! display.flush().unwrap();
-//!
-//! let text_style = MonoTextStyleBuilder::new()
-//!     .font(&FONT_6X10)
-//!     .text_color(BinaryColor::On)
-//!     .build();
-//!
-//! Text::with_baseline("Hello world!", Point::zero(), text_style, Baseline::Top)
-//!     .draw(&mut display)
-//!     .unwrap();
-//!
-//! Text::with_baseline("Hello Rust!", Point::new(0, 16), text_style, Baseline::Top)
-//!     .draw(&mut display)
-//!     .unwrap();
-//!
-//! display.flush().unwrap();
-//! ```
 
+// The examples draw through `DrawTarget`, which needs the graphics feature
+#![cfg_attr(
+    feature = "graphics",
+    doc = r##"
+# Examples
+
+## Draw some text to the display
+
+Uses [mode::GraphicsMode] and [embedded_graphics](../embedded_graphics/index.html).
+
+```rust,no_run
+# use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+# struct Interface;
+# impl AsyncWriteOnlyDataCommand for Interface {
+#     async fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+#         Ok(())
+#     }
+#     async fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+#         Ok(())
+#     }
+# }
+# struct Delay;
+# impl embedded_hal_async::delay::DelayNs for Delay {
+#     async fn delay_ns(&mut self, _: u32) {}
+# }
+# async fn example(display_interface: Interface, mut delay: Delay) {
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Baseline, Text},
+};
+use oled_async::{prelude::*, Builder};
+
+let mut display: GraphicsMode<_, _, [u8; 128 * 128 / 8]> =
+    Builder::new(oled_async::displays::sh1107::Sh1107_128_128 {})
+        .with_rotation(DisplayRotation::Rotate180)
+        .connect(display_interface)
+        .into();
+
+display.init(&mut delay).await.unwrap();
+display.flush().await.unwrap();
+
+let text_style = MonoTextStyleBuilder::new()
+    .font(&FONT_6X10)
+    .text_color(BinaryColor::On)
+    .build();
+
+Text::with_baseline("Hello world!", Point::zero(), text_style, Baseline::Top)
+    .draw(&mut display)
+    .unwrap();
+
+Text::with_baseline("Hello Rust!", Point::new(0, 16), text_style, Baseline::Top)
+    .draw(&mut display)
+    .unwrap();
+
+display.flush().await.unwrap();
+# }
+```
+"##
+)]
 #![no_std]
 #![deny(missing_docs)]
 #![deny(missing_copy_implementations)]
@@ -95,25 +138,28 @@
 #![deny(unused_import_braces)]
 #![deny(unused_qualifications)]
 
-/// Errors in this crate
-#[derive(Debug)]
-pub enum Error<CommE, PinE> {
-    /// Communication error
-    Comm(CommE),
-    /// Pin setting error
-    Pin(PinE),
-}
-
 extern crate embedded_hal as hal;
 
+#[cfg(any(test, feature = "blocking"))]
+pub mod blocking;
 pub mod builder;
-mod command;
+pub mod command;
+pub mod config;
 pub mod display;
 pub mod displayrotation;
 pub mod displays;
+#[cfg(any(test, feature = "emulator"))]
+pub mod emulator;
+pub mod error;
+pub mod init;
+pub mod interface;
 pub mod mode;
 pub mod prelude;
 pub mod properties;
-#[doc(hidden)]
-//pub mod test_helpers;
+#[cfg(test)]
+mod test_helpers;
+
 pub use crate::builder::{Builder, NoOutputPin};
+pub use crate::command::{Command, CommandBatch, NFrames, ScrollDirection, VcomhLevel};
+pub use crate::config::DisplayConfig;
+pub use crate::error::Error;
@@ -2,37 +2,47 @@
 
 use display_interface::AsyncWriteOnlyDataCommand;
 
-use crate::properties::DisplayProperties;
+use crate::{builder::NoOutputPin, properties::DisplayProperties};
 
 /// Display mode abstraction
 pub struct DisplayMode<MODE>(pub MODE);
 
 /// Trait with core functionality for display mode switching
-pub trait DisplayModeTrait<DV, DI> {
+pub trait DisplayModeTrait<DV, DI, RST = NoOutputPin> {
     /// Allocate all required data and initialise display for mode
-    fn new(properties: DisplayProperties<DV, DI>) -> Self;
+    fn new(properties: DisplayProperties<DV, DI, RST>) -> Self;
 
     /// Release resources for reuse with different mode
-    fn release(self) -> DisplayProperties<DV, DI>;
+    fn release(self) -> DisplayProperties<DV, DI, RST>;
 }
 
 impl<MODE> DisplayMode<MODE> {
     /// Setup display to run in requested mode
-    pub fn new<DV, DI>(properties: DisplayProperties<DV, DI>) -> Self
+    pub fn new<DV, DI, RST>(properties: DisplayProperties<DV, DI, RST>) -> Self
     where
         DI: AsyncWriteOnlyDataCommand,
-        MODE: DisplayModeTrait<DV, DI>,
+        MODE: DisplayModeTrait<DV, DI, RST>,
     {
         DisplayMode(MODE::new(properties))
     }
 
+    /// Release the display properties, e.g. to construct a mode with caller provided storage
+    /// such as [`GraphicsMode::with_buffer`](crate::mode::GraphicsMode::with_buffer)
+    pub fn release<DV, DI, RST>(self) -> DisplayProperties<DV, DI, RST>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        MODE: DisplayModeTrait<DV, DI, RST>,
+    {
+        self.0.release()
+    }
+
     /// Change into any mode implementing DisplayModeTrait
     // TODO: Figure out how to stay as generic DisplayMode but act as particular mode
-    pub fn into<DV, DI, NMODE: DisplayModeTrait<DV, DI>>(self) -> NMODE
+    pub fn into<DV, DI, RST, NMODE: DisplayModeTrait<DV, DI, RST>>(self) -> NMODE
     where
         DI: AsyncWriteOnlyDataCommand,
         DV: crate::display::DisplayVariant,
-        MODE: DisplayModeTrait<DV, DI>,
+        MODE: DisplayModeTrait<DV, DI, RST>,
     {
         let properties = self.0.release();
         NMODE::new(properties)
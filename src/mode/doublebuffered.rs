@@ -0,0 +1,532 @@
+//! Double buffered display module that renders the next frame while the last one is sent
+//!
+//! The application draws into the back buffer. [`DoubleBufferedMode::swap`] compares it with the
+//! front buffer, which holds what the display shows, copies it over and hands out the front
+//! buffer to be sent alongside a [`BackBuffer`] to keep drawing into. Only the column span of each
+//! page that differs between the two frames goes out over the bus.
+//!
+//! ```rust,ignore
+//! use embassy_futures::join::join;
+//!
+//! let mut disp: DoubleBufferedMode<_, _, [u8; Display::BUFFER_SIZE]> =
+//!     Builder::new(Display {}).connect(display_interface).into();
+//! disp.init(&mut delay).await.unwrap();
+//!
+//! loop {
+//!     let (flush, mut back) = disp.swap();
+//!     // Frame N is transmitted while frame N + 1 is rendered
+//!     let (result, _) = join(flush.send(), async {
+//!         back.clear();
+//!         render(&mut back);
+//!     })
+//!     .await;
+//!     result.unwrap();
+//! }
+//! ```
+
+use display_interface::AsyncWriteOnlyDataCommand;
+use embedded_hal_async::delay::DelayNs;
+use hal::digital::OutputPin;
+
+use crate::{
+    builder::NoOutputPin,
+    display::{self, PixelFormat},
+    displayrotation::DisplayRotation,
+    mode::{
+        displaymode::DisplayModeTrait,
+        graphics::{
+            rotated_size, set_buffer_pixel, write_pages, Separate, CLEAN, DEFAULT_BUFFER_SIZE,
+            MAX_PAGES,
+        },
+    },
+    properties::DisplayProperties,
+    Error,
+};
+
+/// Double buffered graphics mode handler
+///
+/// Both buffers are owned `[u8; N]` arrays by default. Any other storage implementing
+/// `AsMut<[u8]>` can be used through [`DoubleBufferedMode::with_buffers`].
+pub struct DoubleBufferedMode<DV, DI, B = [u8; DEFAULT_BUFFER_SIZE], RST = NoOutputPin>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+    B: AsMut<[u8]>,
+{
+    properties: DisplayProperties<DV, DI, RST>,
+    front: B,
+    back: B,
+    /// The display RAM does not match the front buffer, e.g. after init or a failed flush
+    resend_all: bool,
+}
+
+impl<DV, DI, const BS: usize, RST> DisplayModeTrait<DV, DI, RST>
+    for DoubleBufferedMode<DV, DI, [u8; BS], RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    /// Create new DoubleBufferedMode instance. Fails to build if `BS` is smaller than
+    /// `DV::BUFFER_SIZE`.
+    fn new(properties: DisplayProperties<DV, DI, RST>) -> Self {
+        const {
+            assert!(
+                BS >= DV::BUFFER_SIZE,
+                "DoubleBufferedMode buffer is too small for the display variant"
+            )
+        };
+
+        Self::new_unchecked(properties, [0u8; BS], [0u8; BS])
+    }
+
+    /// Release all resources used by DoubleBufferedMode
+    fn release(self) -> DisplayProperties<DV, DI, RST> {
+        self.properties
+    }
+}
+
+impl<DV, DI, B, RST> DoubleBufferedMode<DV, DI, B, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+    B: AsMut<[u8]>,
+{
+    /// Create a DoubleBufferedMode instance using caller provided storage for both buffers.
+    /// Fails with [`Error::BufferTooSmall`] if either is shorter than
+    /// [`DisplayVariant::BUFFER_SIZE`](display::DisplayVariant::BUFFER_SIZE).
+    pub fn with_buffers(
+        properties: DisplayProperties<DV, DI, RST>,
+        mut front: B,
+        mut back: B,
+    ) -> Result<Self, Error> {
+        if front.as_mut().len() < DV::BUFFER_SIZE || back.as_mut().len() < DV::BUFFER_SIZE {
+            return Err(Error::BufferTooSmall);
+        }
+
+        Ok(Self::new_unchecked(properties, front, back))
+    }
+
+    fn new_unchecked(properties: DisplayProperties<DV, DI, RST>, front: B, back: B) -> Self {
+        const {
+            assert!(
+                matches!(DV::PIXEL_FORMAT, PixelFormat::Mono),
+                "DoubleBufferedMode requires a Mono display variant"
+            )
+        };
+
+        DoubleBufferedMode {
+            properties,
+            front,
+            back,
+            // Display RAM content is unknown, so the first flush must write everything
+            resend_all: true,
+        }
+    }
+
+    /// Release the display properties and the front and back buffer storage
+    pub fn release_buffers(self) -> (DisplayProperties<DV, DI, RST>, B, B) {
+        (self.properties, self.front, self.back)
+    }
+
+    /// Access the back buffer for drawing
+    pub fn back_buffer(&mut self) -> BackBuffer<'_> {
+        BackBuffer {
+            buffer: self.back.as_mut(),
+            size: self.properties.get_variant().size(),
+            rotation: self.properties.get_rotation(),
+        }
+    }
+
+    /// Clear the back buffer. You need to call `swap_and_flush` for any effect on the screen
+    pub fn clear(&mut self) {
+        self.back_buffer().clear();
+    }
+
+    /// Turn a pixel of the back buffer on or off, see [`BackBuffer::set_pixel`]
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
+        self.back_buffer().set_pixel(x, y, value);
+    }
+
+    /// Make the back buffer the new front buffer and return the pending transfer of its changes
+    /// together with the back buffer to draw the next frame into. The back buffer keeps the
+    /// frame just swapped in. If the returned [`PendingFlush`] is dropped or fails, the next swap
+    /// rewrites the whole display.
+    pub fn swap(&mut self) -> (PendingFlush<'_, DV, DI, RST>, BackBuffer<'_>) {
+        let (display_width, display_height) = self.properties.get_variant().size();
+        let rotation = self.properties.get_rotation();
+        let width = display_width as usize;
+        let pages = (display_height as usize).div_ceil(8);
+        let front = self.front.as_mut();
+        let back = self.back.as_mut();
+
+        let mut spans = [CLEAN; MAX_PAGES];
+        for (page, span) in spans[..pages].iter_mut().enumerate() {
+            let columns = page * width..(page + 1) * width;
+            if self.resend_all {
+                *span = (0, (width - 1) as u8);
+                continue;
+            }
+
+            let changed = |(old, new): (&u8, &u8)| old != new;
+            let front = &front[columns.clone()];
+            let back = &back[columns];
+            if let Some(start) = front.iter().zip(back).position(changed) {
+                let end = front.iter().zip(back).rposition(changed).unwrap_or(start);
+                *span = (start as u8, end as u8);
+            }
+        }
+
+        let size = width * pages;
+        front[..size].copy_from_slice(&back[..size]);
+        self.resend_all = true;
+
+        (
+            PendingFlush {
+                properties: &mut self.properties,
+                front,
+                spans,
+                resend_all: &mut self.resend_all,
+            },
+            BackBuffer {
+                buffer: back,
+                size: (display_width, display_height),
+                rotation,
+            },
+        )
+    }
+
+    /// Swap the buffers and send the changes of the frame drawn since the last swap
+    pub async fn swap_and_flush(&mut self) -> Result<(), Error> {
+        self.swap().0.send().await
+    }
+
+    /// Reset the display through the reset pin given to the
+    /// [`Builder`](crate::Builder::with_reset_pin). The display must be initialised again
+    /// afterwards.
+    pub async fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        RST: OutputPin,
+    {
+        self.properties.reset(delay).await
+    }
+
+    /// Reset the display, then initialise it
+    pub async fn reset_and_init<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        RST: OutputPin,
+    {
+        self.reset(delay).await?;
+        self.init(delay).await?;
+
+        Ok(())
+    }
+
+    /// Initialise the display in column mode. The next swap rewrites the whole display.
+    pub async fn init<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.properties.init_column_mode(delay).await?;
+        self.resend_all = true;
+
+        Ok(())
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn get_dimensions(&self) -> (u16, u16) {
+        self.properties.get_dimensions()
+    }
+
+    /// Get the display rotation
+    pub fn get_rotation(&self) -> DisplayRotation {
+        self.properties.get_rotation()
+    }
+
+    /// Set the display rotation
+    pub async fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), Error> {
+        // Display RAM is written in the new orientation, so all of it has to be resent
+        self.resend_all = true;
+        self.properties.set_rotation(rot).await
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub async fn display_on(&mut self, on: bool) -> Result<(), Error> {
+        self.properties.display_on(on).await
+    }
+
+    /// Turn the display and its charge pump off. The display RAM keeps its content.
+    pub async fn sleep(&mut self) -> Result<(), Error> {
+        self.properties.sleep().await
+    }
+
+    /// Turn the charge pump and the display back on after `sleep`
+    pub async fn wake<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.properties.wake(delay).await
+    }
+
+    /// Shut the display down and switch off the optional VCC and VDD enable pins, see
+    /// [`DisplayProperties::power_down`]
+    pub async fn power_down<VDD, VCC, D>(
+        &mut self,
+        vdd: &mut VDD,
+        vcc: &mut VCC,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        VDD: OutputPin,
+        VCC: OutputPin,
+        D: DelayNs,
+    {
+        self.properties.power_down(vdd, vcc, delay).await
+    }
+
+    /// Switch the optional VDD and VCC enable pins back on after `power_down`. Call `init`
+    /// afterwards.
+    pub async fn power_up<VDD, VCC, D>(
+        &mut self,
+        vdd: &mut VDD,
+        vcc: &mut VCC,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        VDD: OutputPin,
+        VCC: OutputPin,
+        D: DelayNs,
+    {
+        self.properties.power_up(vdd, vcc, delay).await
+    }
+
+    /// Set the display contrast
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
+        self.properties.set_contrast(contrast).await
+    }
+}
+
+/// Transfer of the front buffer changes, returned by [`DoubleBufferedMode::swap`]
+pub struct PendingFlush<'a, DV, DI, RST> {
+    properties: &'a mut DisplayProperties<DV, DI, RST>,
+    front: &'a [u8],
+    spans: [(u8, u8); MAX_PAGES],
+    resend_all: &'a mut bool,
+}
+
+impl<DV, DI, RST> PendingFlush<'_, DV, DI, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    /// Send the column span of every page that changed. A frame changed on every column of
+    /// every page is sent as a single draw area.
+    pub async fn send(self) -> Result<(), Error> {
+        let (display_width, display_height) = self.properties.get_variant().size();
+        let pages = (display_height as usize).div_ceil(8);
+
+        write_pages::<Separate, _, _, _>(
+            self.properties,
+            &self.front[..display_width as usize * pages],
+            &self.spans[..pages],
+        )
+        .await?;
+        *self.resend_all = false;
+
+        Ok(())
+    }
+}
+
+/// Back buffer of a [`DoubleBufferedMode`] to draw the next frame into
+pub struct BackBuffer<'a> {
+    buffer: &'a mut [u8],
+    size: (u16, u16),
+    rotation: DisplayRotation,
+}
+
+impl BackBuffer<'_> {
+    /// Clear the back buffer
+    pub fn clear(&mut self) {
+        self.buffer.fill(0);
+    }
+
+    /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off. If the X and Y
+    /// coordinates are out of the bounds of the display, this method call is a noop.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
+        set_buffer_pixel(self.buffer, self.size, self.rotation, x, y, value);
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn get_dimensions(&self) -> (u16, u16) {
+        rotated_size(self.size, self.rotation)
+    }
+}
+
+#[cfg(feature = "graphics")]
+use crate::mode::graphics::draw_pixels;
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+    Pixel,
+};
+
+#[cfg(feature = "graphics")]
+impl DrawTarget for BackBuffer<'_> {
+    type Color = BinaryColor;
+    type Error = Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+        draw_pixels(bb, pixels, |x, y, value| self.set_pixel(x, y, value));
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl OriginDimensions for BackBuffer<'_> {
+    fn size(&self) -> Size {
+        let (w, h) = self.get_dimensions();
+
+        Size::new(w.into(), h.into())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DV, DI, B, RST> DrawTarget for DoubleBufferedMode<DV, DI, B, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]>,
+    DV: display::DisplayVariant,
+{
+    type Color = BinaryColor;
+    type Error = Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.back_buffer().draw_iter(pixels)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DV, DI, B, RST> OriginDimensions for DoubleBufferedMode<DV, DI, B, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]>,
+    DV: display::DisplayVariant,
+{
+    fn size(&self) -> Size {
+        let (w, h) = self.get_dimensions();
+
+        Size::new(w.into(), h.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{
+        config::DisplayConfig,
+        displays::{sh1106::Sh1106_128_64, ssd1306::Ssd1306_128_32},
+        test_helpers::{block_on, commands, data, MockInterface, Transfer},
+    };
+
+    fn double_buffered<DV: display::DisplayVariant>(
+        variant: DV,
+        iface: &MockInterface,
+    ) -> DoubleBufferedMode<DV, MockInterface> {
+        DoubleBufferedMode::new(DisplayProperties::new(
+            variant,
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        ))
+    }
+
+    #[test]
+    fn swap_and_flush() {
+        let iface = MockInterface::new();
+        let mut display = double_buffered(Ssd1306_128_32 {}, &iface);
+
+        // The first frame rewrites the whole display
+        display.set_pixel(0, 0, 1);
+        block_on(display.swap_and_flush()).unwrap();
+
+        let mut frame = [0; 128 * 32 / 8];
+        frame[0] = 1;
+        assert_eq!(
+            iface.take(),
+            [commands([&[0x21, 0, 127], &[0x22, 0, 3]]), data(&frame)]
+        );
+
+        // Only the differing span of the changed page is sent
+        display.set_pixel(0, 0, 0);
+        display.set_pixel(10, 20, 1);
+        display.set_pixel(12, 23, 1);
+        block_on(display.swap_and_flush()).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                commands([&[0x21, 0, 0], &[0x22, 0, 0]]),
+                data(&[0]),
+                commands([&[0x21, 10, 12], &[0x22, 2, 2]]),
+                data(&[0x10, 0x00, 0x80]),
+            ]
+        );
+
+        // Redrawing the same frame sends nothing
+        display.clear();
+        display.set_pixel(10, 20, 1);
+        display.set_pixel(12, 23, 1);
+        block_on(display.swap_and_flush()).unwrap();
+        assert_eq!(iface.take(), []);
+    }
+
+    #[test]
+    fn draw_while_sending() {
+        let iface = MockInterface::new();
+        let mut display = double_buffered(Sh1106_128_64 {}, &iface);
+
+        display.set_pixel(0, 0, 1);
+        let (flush, mut back) = display.swap();
+        // Drawing the next frame does not change the frame being sent
+        back.set_pixel(1, 0, 1);
+        block_on(flush.send()).unwrap();
+
+        let transfers = iface.take();
+        assert_eq!(transfers.len(), 16);
+        assert_eq!(transfers[1], data(&[&[1][..], &[0; 127]].concat()));
+
+        block_on(display.swap_and_flush()).unwrap();
+        assert_eq!(
+            iface.take(),
+            [commands([&[0xB0], &[0x03], &[0x10]]), data(&[0x01])]
+        );
+    }
+
+    #[test]
+    fn dropped_flush_resends_all() {
+        let iface = MockInterface::new();
+        let mut display = double_buffered(Sh1106_128_64 {}, &iface);
+
+        block_on(display.swap_and_flush()).unwrap();
+        iface.take();
+
+        display.set_pixel(5, 5, 1);
+        let _ = display.swap();
+        block_on(display.swap_and_flush()).unwrap();
+
+        let pages: Vec<_> = iface
+            .take()
+            .into_iter()
+            .filter(|transfer| matches!(transfer, Transfer::Data(data) if data.len() == 128))
+            .collect();
+        assert_eq!(pages.len(), 8);
+    }
+}
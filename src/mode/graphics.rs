@@ -1,6 +1,6 @@
 //! Buffered display module for use with the [embedded-graphics] crate
 //!
-//! ```rust,no_run
+//! ```rust,ignore
 //!
 //! use embedded_graphics::{
 //!     mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
@@ -9,13 +9,13 @@
 //!     text::{Baseline, Text},
 //! };
 //! async fn run_display(display_interface: SomeInstanceOfDisplayInterface) {
-//!     let mut disp: GraphicsMode<_, _> = Builder::new(Display {})
+//!     let mut disp: GraphicsMode<_, _, [u8; Display::BUFFER_SIZE], _> = Builder::new(Display {})
 //!         .with_rotation(crate::DisplayRotation::Rotate180)
+//!         .with_reset_pin(reset)
 //!         .connect(display_interface)
 //!         .into();
 //!
-//!     disp.reset(&mut reset, &mut delay).unwrap();
-//!     disp.init().await.unwrap();
+//!     disp.reset_and_init(&mut delay).await.unwrap();
 //!     disp.clear();
 //!     disp.flush().await.unwrap();
 //!
@@ -31,144 +31,394 @@
 //! }
 //! ```
 
-use display_interface::{AsyncWriteOnlyDataCommand, DisplayError};
-use hal::{delay::DelayNs, digital::OutputPin};
+use core::ops::RangeInclusive;
+
+use display_interface::AsyncWriteOnlyDataCommand;
+use embedded_hal_async::delay::DelayNs;
+use hal::digital::OutputPin;
 
 use crate::{
-    display, displayrotation::DisplayRotation, mode::displaymode::DisplayModeTrait,
+    builder::NoOutputPin,
+    command::{NFrames, ScrollDirection},
+    display::{self, HardwareScroll, OneColumnScroll, PixelFormat},
+    displayrotation::DisplayRotation,
+    interface::WriteCommandsAndData,
+    mode::displaymode::DisplayModeTrait,
     properties::DisplayProperties,
+    Error,
 };
 
-const DEFAULT_BUFFER_SIZE: usize = 160 * 160 / 8;
+pub(super) const DEFAULT_BUFFER_SIZE: usize = 160 * 160 / 8;
+/// Number of pages covered by the dirty area tracking, enough for any display height
+pub(super) const MAX_PAGES: usize = (u8::MAX as usize + 1) / 8;
+/// Inclusive (min, max) dirty column span of a page without changes
+pub(super) const CLEAN: (u8, u8) = (u8::MAX, 0);
+/// Default cost in bytes of starting a new draw area, see [`GraphicsMode::set_run_overhead`]
+pub const DEFAULT_RUN_OVERHEAD: usize = 6;
 
 /// Graphics mode handler
-pub struct GraphicsMode<DV, DI, const BS: usize = DEFAULT_BUFFER_SIZE>
+///
+/// The framebuffer `B` is an owned `[u8; N]` array by default. Any other storage implementing
+/// `AsMut<[u8]>`, e.g. a `&'static mut [u8]` placed in a DMA capable RAM region, can be used
+/// through [`GraphicsMode::with_buffer`].
+///
+/// The optional shadow `S` holds a copy of what was last sent to the display, see
+/// [`GraphicsMode::with_shadow`]. The default `[u8; 0]` disables it.
+pub struct GraphicsMode<DV, DI, B = [u8; DEFAULT_BUFFER_SIZE], RST = NoOutputPin, S = [u8; 0]>
 where
     DI: AsyncWriteOnlyDataCommand,
     DV: display::DisplayVariant,
+    B: AsMut<[u8]>,
+    S: AsMut<[u8]>,
 {
-    properties: DisplayProperties<DV, DI>,
-    buffer: [u8; BS],
+    properties: DisplayProperties<DV, DI, RST>,
+    buffer: B,
+    dirty: [(u8, u8); MAX_PAGES],
+    shadow: S,
+    /// The shadow matches the display RAM
+    shadow_valid: bool,
+    run_overhead: usize,
 }
 
-impl<DV, DI, const BS: usize> DisplayModeTrait<DV, DI> for GraphicsMode<DV, DI, BS>
+impl<DV, DI, const BS: usize, RST> DisplayModeTrait<DV, DI, RST>
+    for GraphicsMode<DV, DI, [u8; BS], RST>
 where
     DI: AsyncWriteOnlyDataCommand,
     DV: display::DisplayVariant,
 {
-    /// Create new GraphicsMode instance
-    fn new(properties: DisplayProperties<DV, DI>) -> Self {
-        GraphicsMode {
-            properties,
-            buffer: [0u8; BS],
-        }
+    /// Create new GraphicsMode instance. Fails to build if `BS` is smaller than
+    /// `DV::BUFFER_SIZE`.
+    ///
+    /// ```rust,compile_fail
+    /// # use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+    /// # use oled_async::{displays::sh1108::Sh1108_128_160, prelude::*, Builder};
+    /// # struct Interface;
+    /// # impl AsyncWriteOnlyDataCommand for Interface {
+    /// #     async fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+    /// #         Ok(())
+    /// #     }
+    /// #     async fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    /// // A 128x160 display needs 128 * 160 / 8 bytes, not 128 * 128 / 8
+    /// let display: GraphicsMode<_, _, [u8; 128 * 128 / 8]> =
+    ///     Builder::new(Sh1108_128_160 {}).connect(Interface).into();
+    /// ```
+    fn new(properties: DisplayProperties<DV, DI, RST>) -> Self {
+        const {
+            assert!(
+                BS >= DV::BUFFER_SIZE,
+                "GraphicsMode buffer is too small for the display variant"
+            )
+        };
+
+        GraphicsMode::new_unchecked(properties, [0u8; BS])
     }
 
     /// Release all resources used by GraphicsMode
-    fn release(self) -> DisplayProperties<DV, DI> {
+    fn release(self) -> DisplayProperties<DV, DI, RST> {
         self.properties
     }
 }
 
-impl<DV, DI, const BS: usize> GraphicsMode<DV, DI, BS>
+impl<DV, DI, B, RST> GraphicsMode<DV, DI, B, RST>
 where
     DI: AsyncWriteOnlyDataCommand,
     DV: display::DisplayVariant,
+    B: AsMut<[u8]>,
 {
+    /// Create a GraphicsMode instance drawing into caller provided storage. Fails with
+    /// [`Error::BufferTooSmall`] if `buffer` is shorter than
+    /// [`DisplayVariant::BUFFER_SIZE`](display::DisplayVariant::BUFFER_SIZE).
+    ///
+    /// ```rust,ignore
+    /// static BUFFER: StaticCell<[u8; 128 * 64 / 8]> = StaticCell::new();
+    ///
+    /// let raw_disp = Builder::new(Sh1106_128_64 {}).connect(display_interface);
+    /// let mut display =
+    ///     GraphicsMode::with_buffer(raw_disp.release(), BUFFER.init([0; 128 * 64 / 8])).unwrap();
+    /// ```
+    pub fn with_buffer(
+        properties: DisplayProperties<DV, DI, RST>,
+        mut buffer: B,
+    ) -> Result<Self, Error> {
+        if buffer.as_mut().len() < DV::BUFFER_SIZE {
+            return Err(Error::BufferTooSmall);
+        }
+
+        Ok(Self::new_unchecked(properties, buffer))
+    }
+
+    fn new_unchecked(properties: DisplayProperties<DV, DI, RST>, buffer: B) -> Self {
+        const {
+            assert!(
+                matches!(DV::PIXEL_FORMAT, PixelFormat::Mono),
+                "GraphicsMode requires a Mono display variant"
+            )
+        };
+
+        let mut mode = GraphicsMode {
+            properties,
+            buffer,
+            dirty: [CLEAN; MAX_PAGES],
+            shadow: [],
+            shadow_valid: false,
+            run_overhead: DEFAULT_RUN_OVERHEAD,
+        };
+        // Display RAM content is unknown, so the first flush must write everything
+        mode.mark_all_dirty();
+        mode
+    }
+}
+
+impl<DV, DI, B, RST, S> GraphicsMode<DV, DI, B, RST, S>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+    B: AsMut<[u8]>,
+    S: AsMut<[u8]>,
+{
+    /// Keep a copy of what was last sent to the display in `shadow`. `flush` then compares each
+    /// changed page with the copy and only sends the runs of columns that differ, which pays off
+    /// when large primitives are redrawn with few actual changes. Fails with
+    /// [`Error::BufferTooSmall`] if `shadow` is shorter than
+    /// [`DisplayVariant::BUFFER_SIZE`](display::DisplayVariant::BUFFER_SIZE). The next flush
+    /// rewrites the whole display to fill the copy.
+    ///
+    /// ```rust,ignore
+    /// let mut display: GraphicsMode<_, _, [u8; 128 * 64 / 8], _, [u8; 128 * 64 / 8]> =
+    ///     GraphicsMode::new(properties).with_shadow([0; 128 * 64 / 8]).unwrap();
+    /// ```
+    pub fn with_shadow<S2: AsMut<[u8]>>(
+        self,
+        mut shadow: S2,
+    ) -> Result<GraphicsMode<DV, DI, B, RST, S2>, Error> {
+        if shadow.as_mut().len() < DV::BUFFER_SIZE {
+            return Err(Error::BufferTooSmall);
+        }
+
+        let mut mode = GraphicsMode {
+            properties: self.properties,
+            buffer: self.buffer,
+            dirty: self.dirty,
+            shadow,
+            shadow_valid: false,
+            run_overhead: self.run_overhead,
+        };
+        mode.mark_all_dirty();
+        Ok(mode)
+    }
+
+    /// Stop keeping a copy of the display content and release the shadow storage
+    pub fn without_shadow(self) -> (GraphicsMode<DV, DI, B, RST>, S) {
+        (
+            GraphicsMode {
+                properties: self.properties,
+                buffer: self.buffer,
+                dirty: self.dirty,
+                shadow: [],
+                shadow_valid: false,
+                run_overhead: self.run_overhead,
+            },
+            self.shadow,
+        )
+    }
+
+    /// Set the cost in bytes of starting a new draw area when flushing against a shadow, which
+    /// defaults to [`DEFAULT_RUN_OVERHEAD`]. A page is rewritten whole instead of run by run when
+    /// the overhead of its runs would make them cost more than a single write of the page. Raise
+    /// it on buses with a high cost per transfer.
+    pub fn set_run_overhead(&mut self, bytes: usize) {
+        self.run_overhead = bytes;
+    }
+
+    /// Release the display properties and the framebuffer storage
+    pub fn release_buffer(self) -> (DisplayProperties<DV, DI, RST>, B) {
+        (self.properties, self.buffer)
+    }
+
     /// Clear the display buffer. You need to call `display.flush()` for any effect on the screen
     pub fn clear(&mut self) {
-        self.buffer = [0; BS];
+        self.buffer.as_mut().fill(0);
+        self.mark_all_pages();
     }
 
-    /// Reset display
-    pub fn reset<RST, DELAY, PinE>(&mut self, rst: &mut RST, delay: &mut DELAY) -> Result<(), PinE>
+    /// Mark the whole display as changed so the next `flush` rewrites all of it
+    pub fn mark_all_dirty(&mut self) {
+        self.mark_all_pages();
+        self.shadow_valid = false;
+    }
+
+    fn mark_all_pages(&mut self) {
+        let (display_width, display_height) = self.properties.get_variant().size();
+        let pages = (display_height as usize).div_ceil(8);
+
+        self.dirty[..pages].fill((0, (display_width - 1) as u8));
+    }
+
+    /// Copy a sent range of the buffer into the shadow, if there is one
+    fn update_shadow(&mut self, range: RangeInclusive<usize>) {
+        if let Some(shadow) = self.shadow.as_mut().get_mut(range.clone()) {
+            shadow.copy_from_slice(&self.buffer.as_mut()[range]);
+        }
+    }
+
+    fn mark_dirty(&mut self, page: usize, column: u8) {
+        let (start, end) = &mut self.dirty[page];
+        *start = (*start).min(column);
+        *end = (*end).max(column);
+    }
+
+    /// Reset the display through the reset pin given to the
+    /// [`Builder`](crate::Builder::with_reset_pin). The display must be initialised again
+    /// afterwards.
+    pub async fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error>
     where
-        RST: OutputPin<Error = PinE>,
-        DELAY: DelayNs,
+        RST: OutputPin,
     {
-        rst.set_high()?;
-        delay.delay_ms(1);
-        rst.set_low()?;
-        delay.delay_ms(10);
-        rst.set_high()
-    }
-
-    /// Write out data to display
-    pub async fn flush(&mut self) -> Result<(), DisplayError> {
-        // Ensure the display buffer is at the origin of the display before we send the full frame
-        // to prevent accidental offsets
-        let (display_width, display_height) = DV::dimensions();
-        let column_offset = DV::COLUMN_OFFSET;
-        self.properties
-            .set_draw_area(
-                (column_offset, 0),
-                (display_width + column_offset, display_height),
-            )
-            .await?;
+        self.properties.reset(delay).await
+    }
 
-        let length = (display_width as usize) * (display_height as usize) / 8;
+    /// Reset the display, then initialise it
+    pub async fn reset_and_init<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        RST: OutputPin,
+    {
+        self.reset(delay).await?;
+        self.init(delay).await?;
 
-        self.properties.draw(&self.buffer[..length]).await
+        Ok(())
     }
 
-    /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off. If the X and Y
-    /// coordinates are out of the bounds of the display, this method call is a noop.
-    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
-        let (display_width, _) = DV::dimensions();
-        let display_rotation = self.properties.get_rotation();
+    /// Write out the parts of the buffer changed since the last flush to the display. Each page
+    /// is sent as a single span from its leftmost to its rightmost changed column, or with a
+    /// [shadow](GraphicsMode::with_shadow) as the runs of columns that differ from it.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.flush_with::<Separate>().await
+    }
 
-        let idx = match display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
-                if x >= display_width as u32 {
-                    return;
-                }
-                ((y as usize) / 8 * display_width as usize) + (x as usize)
+    async fn flush_with<W: WriteArea<DI>>(&mut self) -> Result<(), Error> {
+        if self.shadow_valid {
+            return self.flush_changed_runs::<W>().await;
+        }
+
+        let (display_width, display_height) = self.properties.get_variant().size();
+        let width = display_width as usize;
+        let pages = (display_height as usize).div_ceil(8);
+
+        write_pages::<W, _, _, _>(
+            &mut self.properties,
+            &self.buffer.as_mut()[..width * pages],
+            &self.dirty[..pages],
+        )
+        .await?;
+
+        for page in 0..pages {
+            let (start, end) = self.dirty[page];
+            if start <= end {
+                let offset = page * width;
+                self.update_shadow(offset + start as usize..=offset + end as usize);
             }
+        }
+        self.dirty = [CLEAN; MAX_PAGES];
+        self.shadow_valid = !self.shadow.as_mut().is_empty();
+
+        Ok(())
+    }
+
+    /// Send the runs of columns in each dirty page that differ from the shadow
+    async fn flush_changed_runs<W: WriteArea<DI>>(&mut self) -> Result<(), Error> {
+        let (display_width, display_height) = self.properties.get_variant().size();
+        let column_offset = self.properties.column_offset();
+        let pages = (display_height as usize).div_ceil(8);
+        let width = display_width as usize;
+
+        for page in 0..pages {
+            let (start, end) = self.dirty[page];
+            if start > end {
+                continue;
+            }
+
+            let offset = page * width;
+            let span = start as usize..=end as usize;
+            let rewrite = self.rewrite_page(page, span.clone());
+            let buffer = &self.buffer.as_mut()[offset..offset + width];
+            let shadow = &self.shadow.as_mut()[offset..offset + width];
 
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
-                if y >= display_width as u32 {
-                    return;
+            if rewrite {
+                W::write_area(
+                    &mut self.properties,
+                    (column_offset, page as u8),
+                    (column_offset + display_width as u8, page as u8 + 1),
+                    buffer,
+                )
+                .await?;
+            } else {
+                for (first, last) in changed_runs(&buffer[span.clone()], &shadow[span.clone()]) {
+                    let (first, last) = (start + first as u8, start + last as u8);
+                    W::write_area(
+                        &mut self.properties,
+                        (column_offset + first, page as u8),
+                        (column_offset + last + 1, page as u8 + 1),
+                        &buffer[first as usize..=last as usize],
+                    )
+                    .await?;
                 }
-                ((x as usize) / 8 * display_width as usize) + (y as usize)
             }
-        };
 
-        if idx >= self.buffer.len() {
-            return;
+            self.update_shadow(offset + start as usize..=offset + end as usize);
+            self.dirty[page] = CLEAN;
         }
 
-        let (byte, bit) = match display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
-                let byte =
-                    &mut self.buffer[((y as usize) / 8 * display_width as usize) + (x as usize)];
-                let bit = 1 << (y % 8);
+        Ok(())
+    }
 
-                (byte, bit)
-            }
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
-                let byte =
-                    &mut self.buffer[((x as usize) / 8 * display_width as usize) + (y as usize)];
-                let bit = 1 << (x % 8);
+    /// Whether the columns in `span` of `page` that differ from the shadow cost more to address
+    /// run by run than rewriting the whole page
+    fn rewrite_page(&mut self, page: usize, span: RangeInclusive<usize>) -> bool {
+        let width = self.properties.get_variant().size().0 as usize;
+        let offset = page * width;
+        let buffer = &self.buffer.as_mut()[offset..offset + width];
+        let shadow = &self.shadow.as_mut()[offset..offset + width];
+        let (count, bytes) = changed_runs(&buffer[span.clone()], &shadow[span])
+            .fold((0, 0), |(count, bytes), (first, last)| {
+                (count + 1, bytes + last - first + 1)
+            });
 
-                (byte, bit)
-            }
-        };
+        count * self.run_overhead + bytes > self.run_overhead + width
+    }
+
+    /// Write out the whole buffer to the display, regardless of what changed
+    pub async fn flush_all(&mut self) -> Result<(), Error> {
+        self.mark_all_dirty();
+        self.flush().await
+    }
+
+    /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off. If the X and Y
+    /// coordinates are out of the bounds of the display, this method call is a noop.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
+        let size = self.properties.get_variant().size();
+        let rotation = self.properties.get_rotation();
 
-        if value == 0 {
-            *byte &= !bit;
-        } else {
-            *byte |= bit;
+        if let Some((page, column)) =
+            set_buffer_pixel(self.buffer.as_mut(), size, rotation, x, y, value)
+        {
+            self.mark_dirty(page, column);
         }
     }
 
     /// Display is set up in column mode, i.e. a byte walks down a column of 8 pixels from
     /// column 0 on the left, to column _n_ on the right
-    pub async fn init(&mut self) -> Result<(), DisplayError> {
-        self.properties.init_column_mode().await
+    pub async fn init<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.properties.init_column_mode(delay).await?;
+        self.mark_all_dirty();
+
+        Ok(())
     }
 
     /// Get display dimensions, taking into account the current rotation of the display
-    pub fn get_dimensions(&self) -> (u8, u8) {
+    pub fn get_dimensions(&self) -> (u16, u16) {
         self.properties.get_dimensions()
     }
 
@@ -178,61 +428,368 @@ where
     }
 
     /// Set the display rotation
-    pub async fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), DisplayError> {
+    pub async fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), Error> {
+        // Display RAM is written in the new orientation, so all of it has to be resent
+        self.mark_all_dirty();
         self.properties.set_rotation(rot).await
     }
 
     /// Turn the display on or off. The display can be drawn to and retains all
     /// of its memory even while off.
-    pub async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+    pub async fn display_on(&mut self, on: bool) -> Result<(), Error> {
         self.properties.display_on(on).await
     }
 
+    /// Turn the display and its charge pump off. The display RAM keeps its content.
+    pub async fn sleep(&mut self) -> Result<(), Error> {
+        self.properties.sleep().await
+    }
+
+    /// Turn the charge pump and the display back on after `sleep`
+    pub async fn wake<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.properties.wake(delay).await
+    }
+
+    /// Shut the display down and switch off the optional VCC and VDD enable pins, see
+    /// [`DisplayProperties::power_down`]
+    pub async fn power_down<VDD, VCC, D>(
+        &mut self,
+        vdd: &mut VDD,
+        vcc: &mut VCC,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        VDD: OutputPin,
+        VCC: OutputPin,
+        D: DelayNs,
+    {
+        self.properties.power_down(vdd, vcc, delay).await
+    }
+
+    /// Switch the optional VDD and VCC enable pins back on after `power_down`. Call `init`
+    /// afterwards.
+    pub async fn power_up<VDD, VCC, D>(
+        &mut self,
+        vdd: &mut VDD,
+        vcc: &mut VCC,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        VDD: OutputPin,
+        VCC: OutputPin,
+        D: DelayNs,
+    {
+        self.properties.power_up(vdd, vcc, delay).await
+    }
+
     /// Set the display contrast
-    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
         self.properties.set_contrast(contrast).await
     }
 }
 
+impl<DV, DI, B, RST, S> GraphicsMode<DV, DI, B, RST, S>
+where
+    DI: WriteCommandsAndData,
+    DV: display::DisplayVariant,
+    B: AsMut<[u8]>,
+    S: AsMut<[u8]>,
+{
+    /// Write out the parts of the buffer changed since the last flush like `flush`, including the
+    /// comparison against a [shadow](GraphicsMode::with_shadow), but send the address commands of
+    /// each draw area in the same transfer as its data. Over an
+    /// [`I2cInterface`](crate::interface::I2cInterface) this halves the number of bus writes.
+    pub async fn flush_combined(&mut self) -> Result<(), Error> {
+        self.flush_with::<Combined>().await
+    }
+}
+
+impl<DV, DI, B, RST, S> GraphicsMode<DV, DI, B, RST, S>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]>,
+    DV: HardwareScroll,
+    S: AsMut<[u8]>,
+{
+    /// Start scrolling the given range of pages horizontally, moving one column every `interval`
+    pub async fn start_horizontal_scroll(
+        &mut self,
+        pages: RangeInclusive<u8>,
+        direction: ScrollDirection,
+        interval: NFrames,
+    ) -> Result<(), Error> {
+        self.properties
+            .start_horizontal_scroll(pages, direction, interval)
+            .await
+    }
+
+    /// Start scrolling the given range of pages horizontally while the vertical scroll area moves
+    /// `vertical_offset` rows every `interval`
+    pub async fn start_vertical_and_horizontal_scroll(
+        &mut self,
+        pages: RangeInclusive<u8>,
+        direction: ScrollDirection,
+        interval: NFrames,
+        vertical_offset: u8,
+    ) -> Result<(), Error> {
+        self.properties
+            .start_vertical_and_horizontal_scroll(pages, direction, interval, vertical_offset)
+            .await
+    }
+
+    /// Set the rows moved by a vertical scroll
+    pub async fn set_vertical_scroll_area(
+        &mut self,
+        fixed_rows: u8,
+        scroll_rows: u8,
+    ) -> Result<(), Error> {
+        self.properties
+            .set_vertical_scroll_area(fixed_rows, scroll_rows)
+            .await
+    }
+
+    /// Stop any running scroll. The next `flush` rewrites the whole display, as the display RAM
+    /// is not preserved while scrolling.
+    pub async fn stop_scroll(&mut self) -> Result<(), Error> {
+        self.mark_all_dirty();
+        self.properties.stop_scroll().await
+    }
+}
+
+impl<DV, DI, B, RST, S> GraphicsMode<DV, DI, B, RST, S>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]>,
+    DV: OneColumnScroll,
+    S: AsMut<[u8]>,
+{
+    /// Scroll the given range of pages by a single column
+    pub async fn scroll_one_column(
+        &mut self,
+        pages: RangeInclusive<u8>,
+        direction: ScrollDirection,
+    ) -> Result<(), Error> {
+        self.properties.scroll_one_column(pages, direction).await
+    }
+}
+
+/// Turn a pixel of the monochrome frame in `buffer` on or off, for a display of `size` in
+/// `rotation`. Returns the page and column of the pixel if its byte changed.
+pub(super) fn set_buffer_pixel(
+    buffer: &mut [u8],
+    size: (u16, u16),
+    rotation: DisplayRotation,
+    x: u32,
+    y: u32,
+    value: u8,
+) -> Option<(usize, u8)> {
+    let (display_width, display_height) = size;
+
+    // Column and row in the unrotated display memory
+    let (column, row) = match rotation {
+        DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (x, y),
+        DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (y, x),
+    };
+
+    if column >= display_width as u32 || row >= display_height as u32 {
+        return None;
+    }
+
+    let page = (row as usize) / 8;
+    let idx = (page * display_width as usize) + (column as usize);
+
+    let byte = buffer.get_mut(idx)?;
+    let bit = 1 << (row % 8);
+    let old = *byte;
+
+    if value == 0 {
+        *byte &= !bit;
+    } else {
+        *byte |= bit;
+    }
+
+    (*byte != old).then_some((page, column as u8))
+}
+
+/// Dimensions of a display of `size` in `rotation`
+pub(super) fn rotated_size(size: (u16, u16), rotation: DisplayRotation) -> (u16, u16) {
+    let (width, height) = size;
+
+    match rotation {
+        DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (width, height),
+        DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (height, width),
+    }
+}
+
+/// How a flush sends a draw area to the display
+pub(super) trait WriteArea<DI> {
+    /// Address the area from `start` to `end` (exclusive), given as (column, page), and send
+    /// `data` to it
+    async fn write_area<DV, RST>(
+        properties: &mut DisplayProperties<DV, DI, RST>,
+        start: (u8, u8),
+        end: (u8, u8),
+        data: &[u8],
+    ) -> Result<(), Error>
+    where
+        DV: display::DisplayVariant;
+}
+
+/// Send the address commands and the data of a draw area as separate transfers
+pub(super) struct Separate;
+
+impl<DI: AsyncWriteOnlyDataCommand> WriteArea<DI> for Separate {
+    async fn write_area<DV, RST>(
+        properties: &mut DisplayProperties<DV, DI, RST>,
+        start: (u8, u8),
+        end: (u8, u8),
+        data: &[u8],
+    ) -> Result<(), Error>
+    where
+        DV: display::DisplayVariant,
+    {
+        properties.set_draw_area(start, end).await?;
+        properties.draw(data).await
+    }
+}
+
+/// Send the address commands of a draw area in the same transfer as its data
+struct Combined;
+
+impl<DI: WriteCommandsAndData> WriteArea<DI> for Combined {
+    async fn write_area<DV, RST>(
+        properties: &mut DisplayProperties<DV, DI, RST>,
+        start: (u8, u8),
+        end: (u8, u8),
+        data: &[u8],
+    ) -> Result<(), Error>
+    where
+        DV: display::DisplayVariant,
+    {
+        properties.draw_combined(start, end, data).await
+    }
+}
+
+/// Send the inclusive column span of each page in `spans` from `frame`. A frame changed on every
+/// column of every page is sent as a single draw area.
+pub(super) async fn write_pages<W, DV, DI, RST>(
+    properties: &mut DisplayProperties<DV, DI, RST>,
+    frame: &[u8],
+    spans: &[(u8, u8)],
+) -> Result<(), Error>
+where
+    W: WriteArea<DI>,
+    DV: display::DisplayVariant,
+    DI: AsyncWriteOnlyDataCommand,
+{
+    let display_width = properties.get_variant().size().0;
+    let column_offset = properties.column_offset();
+
+    if spans
+        .iter()
+        .all(|&span| span == (0, (display_width - 1) as u8))
+    {
+        return W::write_area(
+            properties,
+            (column_offset, 0),
+            (column_offset + display_width as u8, spans.len() as u8),
+            frame,
+        )
+        .await;
+    }
+
+    for (page, &(start, end)) in spans.iter().enumerate() {
+        if start > end {
+            continue;
+        }
+
+        let offset = page * display_width as usize;
+        W::write_area(
+            properties,
+            (column_offset + start, page as u8),
+            (column_offset + end + 1, page as u8 + 1),
+            &frame[offset + start as usize..=offset + end as usize],
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Inclusive (first, last) indices of the runs of bytes that differ between `new` and `old`
+fn changed_runs<'a>(new: &'a [u8], old: &'a [u8]) -> impl Iterator<Item = (usize, usize)> + 'a {
+    let mut next = 0;
+
+    core::iter::from_fn(move || {
+        let first = next
+            + new[next..]
+                .iter()
+                .zip(&old[next..])
+                .position(|(a, b)| a != b)?;
+        let length = new[first..]
+            .iter()
+            .zip(&old[first..])
+            .position(|(a, b)| a == b)
+            .unwrap_or(new.len() - first);
+
+        next = first + length;
+        Some((first, next - 1))
+    })
+}
+
 #[cfg(feature = "graphics")]
 use embedded_graphics_core::{
     draw_target::DrawTarget,
     geometry::{Dimensions, OriginDimensions, Size},
     pixelcolor::BinaryColor,
+    primitives::Rectangle,
     Pixel,
 };
 
 #[cfg(feature = "graphics")]
-impl<DV, DI, const BS: usize> DrawTarget for GraphicsMode<DV, DI, BS>
+impl<DV, DI, B, RST, S> DrawTarget for GraphicsMode<DV, DI, B, RST, S>
 where
     DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]>,
     DV: display::DisplayVariant,
+    S: AsMut<[u8]>,
 {
     type Color = BinaryColor;
-    type Error = DisplayError;
+    type Error = Error;
 
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
         let bb = self.bounding_box();
-
-        pixels
-            .into_iter()
-            .filter(|Pixel(pos, _color)| bb.contains(*pos))
-            .for_each(|Pixel(pos, color)| {
-                self.set_pixel(pos.x as u32, pos.y as u32, color.is_on().into())
-            });
+        draw_pixels(bb, pixels, |x, y, value| self.set_pixel(x, y, value));
 
         Ok(())
     }
 }
 
+/// Pass the pixels inside `bounding_box` to `set_pixel`
 #[cfg(feature = "graphics")]
-impl<DV, DI, const BS: usize> OriginDimensions for GraphicsMode<DV, DI, BS>
+pub(super) fn draw_pixels<I>(
+    bounding_box: Rectangle,
+    pixels: I,
+    mut set_pixel: impl FnMut(u32, u32, u8),
+) where
+    I: IntoIterator<Item = Pixel<BinaryColor>>,
+{
+    pixels
+        .into_iter()
+        .filter(|Pixel(pos, _color)| bounding_box.contains(*pos))
+        .for_each(|Pixel(pos, color)| set_pixel(pos.x as u32, pos.y as u32, color.is_on().into()));
+}
+
+#[cfg(feature = "graphics")]
+impl<DV, DI, B, RST, S> OriginDimensions for GraphicsMode<DV, DI, B, RST, S>
 where
     DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]>,
     DV: display::DisplayVariant,
+    S: AsMut<[u8]>,
 {
     fn size(&self) -> Size {
         let (w, h) = self.get_dimensions();
@@ -240,3 +797,263 @@ where
         Size::new(w.into(), h.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{
+        config::DisplayConfig,
+        displays::{sh1106::Sh1106_128_64, ssd1306::Ssd1306_128_32},
+        test_helpers::{block_on, commands, data, init_transfers, MockInterface, Transfer},
+        Builder,
+    };
+
+    fn graphics_mode<DV: display::DisplayVariant>(
+        variant: DV,
+        iface: &MockInterface,
+    ) -> GraphicsMode<DV, MockInterface> {
+        GraphicsMode::new(DisplayProperties::new(
+            variant,
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        ))
+    }
+
+    #[test]
+    fn flush_page_addressing() {
+        let iface = MockInterface::new();
+        let mut display = graphics_mode(Sh1106_128_64 {}, &iface);
+
+        display.set_pixel(0, 0, 1);
+        block_on(display.flush()).unwrap();
+
+        let mut expected = Vec::new();
+        for page in 0..8 {
+            expected.push(commands([&[0xB0 | page], &[0x02], &[0x10]]));
+            expected.push(Transfer::Data(
+                (0..128).map(|i| (page == 0 && i == 0) as u8).collect(),
+            ));
+        }
+        assert_eq!(iface.take(), expected);
+
+        // Only the changed span of the changed page is sent again
+        display.set_pixel(10, 20, 1);
+        display.set_pixel(12, 23, 1);
+        block_on(display.flush()).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                commands([&[0xB2], &[0x0C], &[0x10]]),
+                data(&[0x10, 0x00, 0x80]),
+            ]
+        );
+
+        // Nothing changed, nothing sent
+        display.set_pixel(10, 20, 1);
+        block_on(display.flush()).unwrap();
+        assert_eq!(iface.take(), []);
+    }
+
+    #[test]
+    fn flush_horizontal_addressing() {
+        let iface = MockInterface::new();
+        let mut display = graphics_mode(Ssd1306_128_32 {}, &iface);
+
+        block_on(display.flush()).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                commands([&[0x21, 0, 127], &[0x22, 0, 3]]),
+                data(&[0; 128 * 32 / 8]),
+            ]
+        );
+
+        display.set_pixel(127, 31, 1);
+        block_on(display.flush()).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [commands([&[0x21, 127, 127], &[0x22, 3, 3]]), data(&[0x80])]
+        );
+    }
+
+    #[test]
+    fn flush_combined() {
+        let iface = MockInterface::new();
+        let mut display = graphics_mode(Sh1106_128_64 {}, &iface);
+
+        display.set_pixel(0, 0, 1);
+        block_on(display.flush_combined()).unwrap();
+
+        let expected: Vec<_> = (0..8)
+            .map(|page| {
+                Transfer::CommandsAndData(
+                    [0xB0 | page, 0x02, 0x10].to_vec(),
+                    (0..128).map(|i| (page == 0 && i == 0) as u8).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(iface.take(), expected);
+
+        display.set_pixel(10, 20, 1);
+        display.set_pixel(12, 23, 1);
+        block_on(display.flush_combined()).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [Transfer::CommandsAndData(
+                [0xB2, 0x0C, 0x10].to_vec(),
+                [0x10, 0x00, 0x80].to_vec()
+            )]
+        );
+    }
+
+    #[test]
+    fn flush_shadow() {
+        let iface = MockInterface::new();
+        let mut display = graphics_mode(Ssd1306_128_32 {}, &iface)
+            .with_shadow([0u8; 128 * 32 / 8])
+            .unwrap();
+
+        block_on(display.flush()).unwrap();
+        iface.take();
+
+        // Only the differing runs within the dirty span are sent
+        display.set_pixel(10, 0, 1);
+        display.set_pixel(11, 0, 1);
+        display.set_pixel(40, 0, 1);
+        block_on(display.flush()).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                commands([&[0x21, 10, 11], &[0x22, 0, 0]]),
+                data(&[0x01, 0x01]),
+                commands([&[0x21, 40, 40], &[0x22, 0, 0]]),
+                data(&[0x01]),
+            ]
+        );
+
+        // Redrawing the same frame sends nothing
+        display.clear();
+        display.set_pixel(10, 0, 1);
+        display.set_pixel(11, 0, 1);
+        display.set_pixel(40, 0, 1);
+        block_on(display.flush()).unwrap();
+        assert_eq!(iface.take(), []);
+
+        // Many short runs fall back to a full page write
+        for x in (0..128).step_by(2) {
+            display.set_pixel(x, 8, 1);
+        }
+        block_on(display.flush()).unwrap();
+
+        let page: Vec<_> = (0..128).map(|x| (x % 2 == 0) as u8).collect();
+        assert_eq!(
+            iface.take(),
+            [
+                commands([&[0x21, 0, 127], &[0x22, 1, 1]]),
+                Transfer::Data(page)
+            ]
+        );
+
+        assert!(matches!(
+            graphics_mode(Ssd1306_128_32 {}, &iface).with_shadow([0u8; 128]),
+            Err(Error::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn flush_combined_shadow() {
+        let iface = MockInterface::new();
+        let mut display = graphics_mode(Ssd1306_128_32 {}, &iface)
+            .with_shadow([0u8; 128 * 32 / 8])
+            .unwrap();
+
+        block_on(display.flush_combined()).unwrap();
+        iface.take();
+
+        display.set_pixel(10, 0, 1);
+        display.set_pixel(40, 0, 1);
+        block_on(display.flush_combined()).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                Transfer::CommandsAndData([0x21, 10, 10, 0x22, 0, 0].to_vec(), [0x01].to_vec()),
+                Transfer::CommandsAndData([0x21, 40, 40, 0x22, 0, 0].to_vec(), [0x01].to_vec()),
+            ]
+        );
+
+        // Redrawing the same frame sends nothing
+        display.clear();
+        display.set_pixel(10, 0, 1);
+        display.set_pixel(40, 0, 1);
+        block_on(display.flush_combined()).unwrap();
+        assert_eq!(iface.take(), []);
+    }
+
+    #[test]
+    fn caller_provided_buffer() {
+        let iface = MockInterface::new();
+        let properties = DisplayProperties::new(
+            Ssd1306_128_32 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+        let mut storage = [0u8; 128 * 32 / 8];
+
+        let mut display = GraphicsMode::with_buffer(properties, &mut storage[..]).unwrap();
+        display.set_pixel(3, 9, 1);
+        block_on(display.flush()).unwrap();
+
+        let (_, buffer) = display.release_buffer();
+        assert_eq!(buffer[128 + 3], 0x02);
+        assert_eq!(iface.take()[1], data(buffer));
+    }
+
+    #[test]
+    fn caller_provided_buffer_too_small() {
+        let properties = DisplayProperties::new(
+            Sh1106_128_64 {},
+            MockInterface::new(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+
+        assert!(matches!(
+            GraphicsMode::with_buffer(properties, [0u8; 128 * 32 / 8]),
+            Err(Error::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn reset_and_init() {
+        let iface = MockInterface::new();
+        let mut display: GraphicsMode<_, _, [u8; 128 * 64 / 8], _> = Builder::new(Sh1106_128_64 {})
+            .with_reset_pin(iface.pin("rst"))
+            .connect(iface.clone())
+            .into();
+
+        block_on(display.reset_and_init(&mut iface.clone())).unwrap();
+
+        let mut expected = std::vec![
+            Transfer::Pin("rst", true),
+            Transfer::Delay(1_000_000),
+            Transfer::Pin("rst", false),
+            Transfer::Delay(10_000_000),
+            Transfer::Pin("rst", true),
+            Transfer::Delay(1_000_000),
+        ];
+        expected.extend(init_transfers(Sh1106_128_64 {}));
+        assert_eq!(iface.take(), expected);
+    }
+}
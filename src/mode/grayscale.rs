@@ -0,0 +1,439 @@
+//! Buffered 4 bit grayscale display module for use with the [embedded-graphics] crate
+//!
+//! ```rust,ignore
+//!
+//! use embedded_graphics::{
+//!     mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
+//!     pixelcolor::Gray4,
+//!     prelude::*,
+//!     text::{Baseline, Text},
+//! };
+//! async fn run_display(display_interface: SomeInstanceOfDisplayInterface) {
+//!     let mut disp: GrayscaleMode<_, _> =
+//!         Builder::new(oled_async::displays::ssd1322::Ssd1322_256_64 {})
+//!             .connect(display_interface)
+//!             .into();
+//!
+//!     disp.init(&mut delay).await.unwrap();
+//!     disp.clear();
+//!
+//!     let text_style = MonoTextStyleBuilder::new()
+//!         .font(&FONT_6X10)
+//!         .text_color(Gray4::new(0x8))
+//!         .build();
+//!     Text::with_baseline("Hello world!", Point::zero(), text_style, Baseline::Top)
+//!         .draw(&mut disp)
+//!         .unwrap();
+//!
+//!     disp.flush().await.unwrap();
+//! }
+//! ```
+
+use display_interface::AsyncWriteOnlyDataCommand;
+use embedded_hal_async::delay::DelayNs;
+use hal::digital::OutputPin;
+
+use crate::{
+    builder::NoOutputPin,
+    display::{GrayScaleTable, GrayscaleVariant, PixelFormat},
+    displayrotation::DisplayRotation,
+    mode::displaymode::DisplayModeTrait,
+    properties::DisplayProperties,
+    Error,
+};
+
+const DEFAULT_BUFFER_SIZE: usize = 256 * 64 / 2;
+
+/// Grayscale mode handler
+///
+/// The framebuffer `B` is an owned array by default, or any caller provided storage, see
+/// [`GrayscaleMode::with_buffer`].
+pub struct GrayscaleMode<DV, DI, B = [u8; DEFAULT_BUFFER_SIZE], RST = NoOutputPin>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+    B: AsMut<[u8]>,
+{
+    properties: DisplayProperties<DV, DI, RST>,
+    buffer: B,
+}
+
+impl<DV, DI, const BS: usize, RST> DisplayModeTrait<DV, DI, RST>
+    for GrayscaleMode<DV, DI, [u8; BS], RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+{
+    /// Create new GrayscaleMode instance. Fails to build if `BS` is smaller than
+    /// `DV::BUFFER_SIZE`.
+    fn new(properties: DisplayProperties<DV, DI, RST>) -> Self {
+        const {
+            assert!(
+                BS >= DV::BUFFER_SIZE,
+                "GrayscaleMode buffer is too small for the display variant"
+            )
+        };
+
+        GrayscaleMode::new_unchecked(properties, [0u8; BS])
+    }
+
+    /// Release all resources used by GrayscaleMode
+    fn release(self) -> DisplayProperties<DV, DI, RST> {
+        self.properties
+    }
+}
+
+impl<DV, DI, B, RST> GrayscaleMode<DV, DI, B, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+    B: AsMut<[u8]>,
+{
+    /// Create a GrayscaleMode instance drawing into caller provided storage. Fails with
+    /// [`Error::BufferTooSmall`] if `buffer` is shorter than
+    /// [`DisplayVariant::BUFFER_SIZE`](crate::display::DisplayVariant::BUFFER_SIZE).
+    pub fn with_buffer(
+        properties: DisplayProperties<DV, DI, RST>,
+        mut buffer: B,
+    ) -> Result<Self, Error> {
+        if buffer.as_mut().len() < DV::BUFFER_SIZE {
+            return Err(Error::BufferTooSmall);
+        }
+
+        Ok(Self::new_unchecked(properties, buffer))
+    }
+
+    fn new_unchecked(properties: DisplayProperties<DV, DI, RST>, buffer: B) -> Self {
+        const {
+            assert!(
+                matches!(DV::PIXEL_FORMAT, PixelFormat::Gray4),
+                "GrayscaleMode requires a Gray4 display variant"
+            )
+        };
+
+        GrayscaleMode { properties, buffer }
+    }
+
+    /// Release the display properties and the framebuffer storage
+    pub fn release_buffer(self) -> (DisplayProperties<DV, DI, RST>, B) {
+        (self.properties, self.buffer)
+    }
+
+    /// Clear the display buffer. You need to call `display.flush()` for any effect on the screen
+    pub fn clear(&mut self) {
+        self.buffer.as_mut().fill(0);
+    }
+
+    /// Reset the display through the reset pin given to the
+    /// [`Builder`](crate::Builder::with_reset_pin). The display must be initialised again
+    /// afterwards.
+    pub async fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        RST: OutputPin,
+    {
+        self.properties.reset(delay).await
+    }
+
+    /// Reset the display, then initialise it
+    pub async fn reset_and_init<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        RST: OutputPin,
+    {
+        self.reset(delay).await?;
+        self.init(delay).await?;
+
+        Ok(())
+    }
+
+    /// Write out data to display
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        let (display_width, display_height) = DV::dimensions();
+
+        self.properties
+            .draw_gray_area(
+                (0, 0),
+                (display_width, display_height),
+                &self.buffer.as_mut()[..DV::BUFFER_SIZE],
+            )
+            .await
+    }
+
+    /// Set the gray level of a pixel, from `0` (off) to `15` (brightest). Higher bits of `value`
+    /// are ignored. If the X and Y coordinates are out of the bounds of the display, this method
+    /// call is a noop.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
+        let (display_width, display_height) = DV::dimensions();
+
+        // Column and row in the unrotated display memory
+        let (column, row) = match self.properties.get_rotation() {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (x, y),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (y, x),
+        };
+
+        if column >= display_width as u32 || row >= display_height as u32 {
+            return;
+        }
+
+        let idx = ((row as usize) * (display_width as usize) + (column as usize)) / 2;
+        let byte = &mut self.buffer.as_mut()[idx];
+        let value = 0xF & value;
+
+        if column % 2 == 0 {
+            *byte = (*byte & 0x0F) | (value << 4);
+        } else {
+            *byte = (*byte & 0xF0) | value;
+        }
+    }
+
+    /// Initialise the display with row addressing, column 0 on the left and column
+    /// _(display_width - 1)_ on the right
+    pub async fn init<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.properties.init_column_mode(delay).await
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn get_dimensions(&self) -> (u16, u16) {
+        self.properties.get_dimensions()
+    }
+
+    /// Get the display rotation
+    pub fn get_rotation(&self) -> DisplayRotation {
+        self.properties.get_rotation()
+    }
+
+    /// Set the display rotation
+    pub async fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), Error> {
+        self.properties.set_rotation(rot).await
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub async fn display_on(&mut self, on: bool) -> Result<(), Error> {
+        self.properties.display_on(on).await
+    }
+
+    /// Turn the display and its charge pump off. The display RAM keeps its content.
+    pub async fn sleep(&mut self) -> Result<(), Error> {
+        self.properties.sleep().await
+    }
+
+    /// Turn the charge pump and the display back on after `sleep`
+    pub async fn wake<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.properties.wake(delay).await
+    }
+
+    /// Shut the display down and switch off the optional VCC and VDD enable pins, see
+    /// [`DisplayProperties::power_down`]
+    pub async fn power_down<VDD, VCC, D>(
+        &mut self,
+        vdd: &mut VDD,
+        vcc: &mut VCC,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        VDD: OutputPin,
+        VCC: OutputPin,
+        D: DelayNs,
+    {
+        self.properties.power_down(vdd, vcc, delay).await
+    }
+
+    /// Switch the optional VDD and VCC enable pins back on after `power_down`. Call `init`
+    /// afterwards.
+    pub async fn power_up<VDD, VCC, D>(
+        &mut self,
+        vdd: &mut VDD,
+        vcc: &mut VCC,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        VDD: OutputPin,
+        VCC: OutputPin,
+        D: DelayNs,
+    {
+        self.properties.power_up(vdd, vcc, delay).await
+    }
+
+    /// Set the display contrast
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
+        self.properties.set_contrast(contrast).await
+    }
+}
+
+impl<DV, DI, B, RST> GrayscaleMode<DV, DI, B, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: GrayScaleTable,
+    B: AsMut<[u8]>,
+{
+    /// Set the drive level of gray levels 1 to 15. `None` selects the built in linear table.
+    pub async fn set_gray_scale_table(&mut self, table: Option<&[u8; 15]>) -> Result<(), Error> {
+        self.properties.set_gray_scale_table(table).await
+    }
+}
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    pixelcolor::{Gray4, GrayColor},
+    Pixel,
+};
+
+#[cfg(feature = "graphics")]
+impl<DV, DI, B, RST> DrawTarget for GrayscaleMode<DV, DI, B, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+    B: AsMut<[u8]>,
+{
+    type Color = Gray4;
+    type Error = Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| {
+                self.set_pixel(pos.x as u32, pos.y as u32, color.luma());
+            });
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DV, DI, B, RST> OriginDimensions for GrayscaleMode<DV, DI, B, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+    B: AsMut<[u8]>,
+{
+    fn size(&self) -> Size {
+        let (w, h) = self.get_dimensions();
+
+        Size::new(w.into(), h.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::DisplayConfig,
+        display::DisplayVariant,
+        displays::ssd1322::Ssd1322_256_64,
+        test_helpers::{block_on, command, data, MockInterface},
+    };
+
+    fn grayscale_mode<const BS: usize>(
+        iface: &MockInterface,
+        rotation: DisplayRotation,
+    ) -> GrayscaleMode<Ssd1322_256_64, MockInterface, [u8; BS]> {
+        GrayscaleMode::new(DisplayProperties::new(
+            Ssd1322_256_64 {},
+            iface.clone(),
+            rotation,
+            DisplayConfig::default(),
+        ))
+    }
+
+    #[test]
+    fn set_pixel_packing() {
+        let mut display =
+            grayscale_mode::<{ 256 * 64 / 2 }>(&MockInterface::new(), DisplayRotation::Rotate0);
+
+        // Even columns are in the high nibble, odd columns in the low nibble
+        display.set_pixel(0, 0, 0xA);
+        display.set_pixel(1, 0, 0x5);
+        display.set_pixel(2, 1, 0x1F);
+        assert_eq!(display.buffer[0], 0xA5);
+        assert_eq!(display.buffer[(256 + 2) / 2], 0xF0);
+
+        // Setting one nibble leaves the other alone
+        display.set_pixel(0, 0, 0);
+        assert_eq!(display.buffer[0], 0x05);
+    }
+
+    #[test]
+    fn set_pixel_rotated() {
+        for rotation in [DisplayRotation::Rotate90, DisplayRotation::Rotate270] {
+            let mut display = grayscale_mode::<{ 256 * 64 / 2 }>(&MockInterface::new(), rotation);
+            assert_eq!(display.get_dimensions(), (64, 256));
+
+            // x and y are swapped to the unrotated column 5, row 3
+            display.set_pixel(3, 5, 0x7);
+            assert_eq!(display.buffer[(3 * 256 + 5) / 2], 0x07);
+
+            display.set_pixel(63, 255, 0x1);
+            assert_eq!(display.buffer[(63 * 256 + 255) / 2], 0x01);
+        }
+    }
+
+    #[test]
+    fn set_pixel_out_of_bounds() {
+        let mut display =
+            grayscale_mode::<{ 256 * 64 / 2 }>(&MockInterface::new(), DisplayRotation::Rotate0);
+        display.set_pixel(256, 0, 0xF);
+        display.set_pixel(0, 64, 0xF);
+        assert!(display.buffer.iter().all(|&byte| byte == 0));
+
+        let mut display =
+            grayscale_mode::<{ 256 * 64 / 2 }>(&MockInterface::new(), DisplayRotation::Rotate90);
+        display.set_pixel(64, 0, 0xF);
+        display.set_pixel(0, 256, 0xF);
+        assert!(display.buffer.iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn flush() {
+        let iface = MockInterface::new();
+        // A buffer larger than the display only has its first BUFFER_SIZE bytes sent
+        let mut display = grayscale_mode::<{ 256 * 64 / 2 + 16 }>(&iface, DisplayRotation::Rotate0);
+        display.buffer.fill(0x11);
+        display.set_pixel(255, 63, 0x3);
+        block_on(display.flush()).unwrap();
+
+        let mut frame = [0x11; Ssd1322_256_64::BUFFER_SIZE];
+        frame[Ssd1322_256_64::BUFFER_SIZE - 1] = 0x13;
+        assert_eq!(
+            iface.take(),
+            [
+                command(&[0x15]),
+                data(&[28, 91]),
+                command(&[0x75]),
+                data(&[0, 63]),
+                command(&[0x5C]),
+                data(&frame),
+            ]
+        );
+    }
+
+    #[test]
+    fn caller_provided_buffer() {
+        let properties = || {
+            DisplayProperties::new(
+                Ssd1322_256_64 {},
+                MockInterface::new(),
+                DisplayRotation::Rotate0,
+                DisplayConfig::default(),
+            )
+        };
+        let mut storage = [0u8; 256 * 64 / 2];
+
+        let mut display = GrayscaleMode::with_buffer(properties(), &mut storage[..]).unwrap();
+        display.set_pixel(1, 0, 0x9);
+        drop(display);
+        assert_eq!(storage[0], 0x09);
+
+        assert!(matches!(
+            GrayscaleMode::with_buffer(properties(), [0u8; 256 * 64 / 4]),
+            Err(Error::BufferTooSmall)
+        ));
+    }
+}
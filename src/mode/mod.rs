@@ -4,7 +4,13 @@
 //! methods it exposes. Look at the modes below for more information on what they expose.
 
 pub mod displaymode;
+pub mod doublebuffered;
 pub mod graphics;
+pub mod grayscale;
 pub mod raw;
+pub mod terminal;
 
-pub use self::{graphics::GraphicsMode, raw::RawMode};
+pub use self::{
+    doublebuffered::DoubleBufferedMode, graphics::GraphicsMode, grayscale::GrayscaleMode,
+    raw::RawMode, terminal::TerminalMode,
+};
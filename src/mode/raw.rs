@@ -3,37 +3,190 @@
 //! A display driver instance without high level functionality used as a return type from the
 //! builder. Used as a source to coerce the driver into richer modes like
 //! [`GraphicsMode`](../graphics/index.html).
+//!
+//! It can also drive the display directly without a framebuffer, e.g. to stream tiles or run
+//! commands the driver has no method for:
+//!
+//! ```rust,ignore
+//! let mut disp: RawMode<_, _, _> = Builder::new(Display {})
+//!     .with_reset_pin(reset)
+//!     .connect(display_interface)
+//!     .into();
+//!
+//! disp.reset_and_init(&mut delay).await.unwrap();
+//! disp.fill((0, 0), (128, 8), 0x00).await.unwrap();
+//! disp.set_window((8, 2), (16, 3)).await.unwrap();
+//! disp.write(&tile).await.unwrap();
+//! disp.command(Command::Invert(true)).await.unwrap();
+//! ```
 
 use display_interface::AsyncWriteOnlyDataCommand;
+use embedded_hal_async::delay::DelayNs;
+use hal::digital::OutputPin;
 
-use crate::{display, mode::displaymode::DisplayModeTrait, properties::DisplayProperties};
+use crate::{
+    builder::NoOutputPin, command::Command, display, mode::displaymode::DisplayModeTrait,
+    properties::DisplayProperties, Error,
+};
 
 /// Raw display mode
-pub struct RawMode<DV, DI>
+pub struct RawMode<DV, DI, RST = NoOutputPin>
 where
     DI: AsyncWriteOnlyDataCommand,
 {
-    properties: DisplayProperties<DV, DI>,
+    properties: DisplayProperties<DV, DI, RST>,
 }
 
-impl<DV, DI> DisplayModeTrait<DV, DI> for RawMode<DV, DI>
+impl<DV, DI, RST> DisplayModeTrait<DV, DI, RST> for RawMode<DV, DI, RST>
 where
     DI: AsyncWriteOnlyDataCommand,
 {
     /// Create new RawMode instance
-    fn new(properties: DisplayProperties<DV, DI>) -> Self {
+    fn new(properties: DisplayProperties<DV, DI, RST>) -> Self {
         RawMode { properties }
     }
 
     /// Release all resources used by RawMode
-    fn release(self) -> DisplayProperties<DV, DI> {
+    fn release(self) -> DisplayProperties<DV, DI, RST> {
         self.properties
     }
 }
 
-impl<DV: display::DisplayVariant, DI: AsyncWriteOnlyDataCommand> RawMode<DV, DI> {
+impl<DV: display::DisplayVariant, DI: AsyncWriteOnlyDataCommand, RST> RawMode<DV, DI, RST> {
     /// Create a new raw display mode
-    pub fn new(properties: DisplayProperties<DV, DI>) -> Self {
+    pub fn new(properties: DisplayProperties<DV, DI, RST>) -> Self {
         RawMode { properties }
     }
+
+    /// Initialise the display in column mode, i.e. a byte walks down a column of 8 pixels
+    pub async fn init<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.properties.init_column_mode(delay).await
+    }
+
+    /// Reset the display through the reset pin given to the
+    /// [`Builder`](crate::Builder::with_reset_pin). The display must be initialised again
+    /// afterwards.
+    pub async fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        RST: OutputPin,
+    {
+        self.properties.reset(delay).await
+    }
+
+    /// Reset the display, then initialise it
+    pub async fn reset_and_init<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        RST: OutputPin,
+    {
+        self.reset(delay).await?;
+        self.init(delay).await
+    }
+
+    /// Send a single command to the display
+    pub async fn command(&mut self, command: Command) -> Result<(), Error> {
+        self.properties.send_command(command).await
+    }
+
+    /// Select the window of display RAM that following `write`s fill, as (column, page) with
+    /// `end` being exclusive. Columns start at the column offset of the display variant.
+    pub async fn set_window(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), Error> {
+        self.properties.set_draw_area(start, end).await
+    }
+
+    /// Write page data into the window, one byte per column of 8 pixels, left to right and then
+    /// page by page. Writes continue where the last one stopped and wrap around at the end of
+    /// the window.
+    pub async fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.properties.draw(data).await
+    }
+
+    /// Fill the window from `start` to `end` with `pattern`, e.g. `0x00` to clear it or `0xAA`
+    /// for horizontal stripes. The window stays selected afterwards.
+    pub async fn fill(&mut self, start: (u8, u8), end: (u8, u8), pattern: u8) -> Result<(), Error> {
+        self.set_window(start, end).await?;
+
+        let row = [pattern; u8::MAX as usize];
+        for _ in start.1..end.1 {
+            self.write(&row[..usize::from(end.0 - start.0)]).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the display properties, e.g. to scroll or change the contrast
+    pub fn properties(&mut self) -> &mut DisplayProperties<DV, DI, RST> {
+        &mut self.properties
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::DisplayConfig,
+        displayrotation::DisplayRotation,
+        displays::{sh1106::Sh1106_128_64, ssd1306::Ssd1306_128_32},
+        test_helpers::{block_on, command, commands, data, MockInterface},
+    };
+
+    fn raw_mode<DV: display::DisplayVariant>(
+        variant: DV,
+        iface: &MockInterface,
+    ) -> RawMode<DV, MockInterface> {
+        RawMode::new(DisplayProperties::new(
+            variant,
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        ))
+    }
+
+    #[test]
+    fn command_and_write() {
+        let iface = MockInterface::new();
+        let mut display = raw_mode(Ssd1306_128_32 {}, &iface);
+
+        block_on(display.command(Command::Invert(true))).unwrap();
+        block_on(display.set_window((8, 1), (10, 3))).unwrap();
+        block_on(display.write(&[1, 2, 3, 4])).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                command(&[0xA7]),
+                commands([&[0x21, 8, 9], &[0x22, 1, 2]]),
+                data(&[1, 2, 3, 4]),
+            ]
+        );
+    }
+
+    #[test]
+    fn fill() {
+        let iface = MockInterface::new();
+        let mut display = raw_mode(Sh1106_128_64 {}, &iface);
+
+        block_on(display.fill((2, 6), (5, 8), 0xAA)).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                commands([&[0xB6], &[0x02], &[0x10]]),
+                data(&[0xAA; 3]),
+                commands([&[0xB7], &[0x02], &[0x10]]),
+                data(&[0xAA; 3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_window() {
+        let iface = MockInterface::new();
+        let mut display = raw_mode(Sh1106_128_64 {}, &iface);
+
+        assert!(matches!(
+            block_on(display.fill((0, 0), (128, 8), 0)),
+            Err(Error::InvalidDrawArea)
+        ));
+        assert_eq!(iface.take(), []);
+    }
 }
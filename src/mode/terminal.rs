@@ -0,0 +1,605 @@
+//! Unbuffered text mode with a built in 6x8 pixel ASCII font
+//!
+//! Text is kept in a small character grid rather than a framebuffer and rendered straight into
+//! the display pages, one page per line of text. Writing is done through [`core::fmt::Write`]
+//! and the changed lines are sent to the display by `flush`.
+//!
+//! ```rust,ignore
+//! use core::fmt::Write;
+//! use oled_async::{mode::TerminalMode, Builder};
+//!
+//! let mut term: TerminalMode<_, _> = Builder::new(oled_async::displays::sh1106::Sh1106_128_64 {})
+//!     .connect(display_interface)
+//!     .into();
+//!
+//! term.init(&mut delay).await.unwrap();
+//! writeln!(term, "Booting...").unwrap();
+//! term.flush().await.unwrap();
+//! ```
+//!
+//! Characters are drawn along the display pages, so only `Rotate0` and `Rotate180` give upright
+//! text. When the cursor moves past the last line the text scrolls up. On displays as tall as
+//! their controller's display RAM this is done in hardware by moving the display start line, as
+//! long as the RAM has no more than the 64 rows `StartLine` can address. Otherwise all lines are
+//! redrawn.
+
+use core::fmt;
+
+use display_interface::AsyncWriteOnlyDataCommand;
+use embedded_hal_async::delay::DelayNs;
+use hal::digital::OutputPin;
+
+use crate::{
+    builder::NoOutputPin,
+    display::{self, PixelFormat},
+    displayrotation::DisplayRotation,
+    mode::displaymode::DisplayModeTrait,
+    properties::DisplayProperties,
+    Error,
+};
+
+/// Width of a character cell in pixels, including one column of spacing
+const CHAR_WIDTH: usize = 6;
+/// Room for the character grid of a 160x160 display
+const DEFAULT_CELLS: usize = (160 / CHAR_WIDTH) * (160 / 8);
+/// Longest possible line in pixels
+const MAX_LINE_WIDTH: usize = u8::MAX as usize + 1;
+
+/// Terminal mode handler
+pub struct TerminalMode<DV, DI, const CS: usize = DEFAULT_CELLS, RST = NoOutputPin>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    properties: DisplayProperties<DV, DI, RST>,
+    /// Character grid, one line per page of the display in ring order
+    cells: [u8; CS],
+    /// Cursor position as (column, line) on screen
+    cursor: (u8, u8),
+    /// Line of the character grid shown at the top of the screen
+    top: u8,
+    /// Bit mask of the grid lines that need to be redrawn
+    dirty: u32,
+    /// The display start line needs to be updated
+    scrolled: bool,
+}
+
+impl<DV, DI, const CS: usize, RST> DisplayModeTrait<DV, DI, RST> for TerminalMode<DV, DI, CS, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    /// Create new TerminalMode instance
+    fn new(properties: DisplayProperties<DV, DI, RST>) -> Self {
+        const {
+            assert!(
+                matches!(DV::PIXEL_FORMAT, PixelFormat::Mono),
+                "TerminalMode requires a Mono display variant"
+            );
+            assert!(
+                CS >= (DV::WIDTH as usize / CHAR_WIDTH) * (DV::HEIGHT as usize / 8),
+                "TerminalMode character grid is too small for the display variant"
+            );
+        };
+
+        let mut mode = TerminalMode {
+            properties,
+            cells: [b' '; CS],
+            cursor: (0, 0),
+            top: 0,
+            dirty: 0,
+            scrolled: false,
+        };
+        mode.clear();
+        mode
+    }
+
+    /// Release all resources used by TerminalMode
+    fn release(self) -> DisplayProperties<DV, DI, RST> {
+        self.properties
+    }
+}
+
+impl<DV, DI, const CS: usize, RST> TerminalMode<DV, DI, CS, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    /// Text can be scrolled by moving the display start line when the display RAM is exactly as
+    /// tall as the display and within reach of the `StartLine` command
+    fn hardware_scroll(&self) -> bool {
+        let variant = self.properties.get_variant();
+        let (_, display_height) = variant.size();
+
+        display_height == variant.ram_rows() && display_height <= 64
+    }
+
+    /// Get the size of the terminal as (columns, lines) of characters
+    pub fn get_size(&self) -> (u8, u8) {
+        let (display_width, display_height) = self.properties.get_variant().size();
+
+        (
+            (display_width as usize / CHAR_WIDTH) as u8,
+            (display_height / 8) as u8,
+        )
+    }
+
+    /// Clear the terminal and move the cursor to the top left corner. You need to call
+    /// `display.flush()` for any effect on the screen
+    pub fn clear(&mut self) {
+        self.cells = [b' '; CS];
+        self.cursor = (0, 0);
+        self.top = 0;
+        self.dirty = u32::MAX;
+        self.scrolled = true;
+    }
+
+    /// Get the cursor position as (column, line)
+    pub fn get_cursor(&self) -> (u8, u8) {
+        self.cursor
+    }
+
+    /// Move the cursor to the given column and line. Positions outside the terminal are clamped
+    /// to the last column or line.
+    pub fn set_cursor(&mut self, column: u8, line: u8) {
+        let (columns, lines) = self.get_size();
+
+        self.cursor = (column.min(columns - 1), line.min(lines - 1));
+    }
+
+    /// Write out the lines changed since the last flush to the display
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        if self.scrolled && self.hardware_scroll() {
+            self.properties.set_start_line(self.top * 8).await?;
+        }
+        self.scrolled = false;
+
+        let (_, lines) = self.get_size();
+        for line in 0..lines {
+            if self.dirty & (1 << line) != 0 {
+                self.draw_line(line).await?;
+                self.dirty &= !(1 << line);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reset the display through the reset pin given to the
+    /// [`Builder`](crate::Builder::with_reset_pin). The display must be initialised again
+    /// afterwards.
+    pub async fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        RST: OutputPin,
+    {
+        self.properties.reset(delay).await
+    }
+
+    /// Reset the display, then initialise and clear it
+    pub async fn reset_and_init<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        RST: OutputPin,
+    {
+        self.reset(delay).await?;
+        self.init(delay).await?;
+
+        Ok(())
+    }
+
+    /// Initialise the display and clear it
+    pub async fn init<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.properties.init_column_mode(delay).await?;
+        self.clear();
+        self.flush().await
+    }
+
+    /// Get the display rotation
+    pub fn get_rotation(&self) -> DisplayRotation {
+        self.properties.get_rotation()
+    }
+
+    /// Set the display rotation. Only `Rotate0` and `Rotate180` give upright text.
+    pub async fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), Error> {
+        // Display RAM is written in the new orientation, so all of it has to be resent
+        self.dirty = u32::MAX;
+        self.properties.set_rotation(rot).await
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub async fn display_on(&mut self, on: bool) -> Result<(), Error> {
+        self.properties.display_on(on).await
+    }
+
+    /// Turn the display and its charge pump off. The display RAM keeps its content.
+    pub async fn sleep(&mut self) -> Result<(), Error> {
+        self.properties.sleep().await
+    }
+
+    /// Turn the charge pump and the display back on after `sleep`
+    pub async fn wake<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.properties.wake(delay).await
+    }
+
+    /// Shut the display down and switch off the optional VCC and VDD enable pins, see
+    /// [`DisplayProperties::power_down`]
+    pub async fn power_down<VDD, VCC, D>(
+        &mut self,
+        vdd: &mut VDD,
+        vcc: &mut VCC,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        VDD: OutputPin,
+        VCC: OutputPin,
+        D: DelayNs,
+    {
+        self.properties.power_down(vdd, vcc, delay).await
+    }
+
+    /// Switch the optional VDD and VCC enable pins back on after `power_down`. Call `init`
+    /// afterwards.
+    pub async fn power_up<VDD, VCC, D>(
+        &mut self,
+        vdd: &mut VDD,
+        vcc: &mut VCC,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        VDD: OutputPin,
+        VCC: OutputPin,
+        D: DelayNs,
+    {
+        self.properties.power_up(vdd, vcc, delay).await
+    }
+
+    /// Set the display contrast
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
+        self.properties.set_contrast(contrast).await
+    }
+
+    /// Render a line of the character grid into its display page
+    async fn draw_line(&mut self, line: u8) -> Result<(), Error> {
+        let (display_width, _) = self.properties.get_variant().size();
+        let column_offset = self.properties.column_offset();
+        let (columns, lines) = self.get_size();
+        let columns = columns as usize;
+
+        // With hardware scrolling the grid maps directly to the display RAM, otherwise the top
+        // line of the grid is always drawn to the first page
+        let page = if self.hardware_scroll() {
+            line
+        } else {
+            (line + lines - self.top) % lines
+        };
+
+        let mut data = [0u8; MAX_LINE_WIDTH];
+        let cells = &self.cells[line as usize * columns..(line as usize + 1) * columns];
+        for (&c, glyph) in cells.iter().zip(data.chunks_mut(CHAR_WIDTH)) {
+            glyph[..5].copy_from_slice(&FONT_5X7[(c - b' ') as usize]);
+        }
+
+        self.properties
+            .set_draw_area(
+                (column_offset, page),
+                (column_offset + display_width as u8, page + 1),
+            )
+            .await?;
+        self.properties.draw(&data[..display_width as usize]).await
+    }
+
+    /// Move the cursor to the start of the next line, scrolling if it is on the last line
+    fn newline(&mut self) {
+        let (columns, lines) = self.get_size();
+
+        self.cursor.0 = 0;
+        if self.cursor.1 + 1 < lines {
+            self.cursor.1 += 1;
+            return;
+        }
+
+        // The old top line of the grid becomes the new bottom line
+        let line = self.top as usize;
+        let columns = columns as usize;
+        self.cells[line * columns..(line + 1) * columns].fill(b' ');
+        self.top = (self.top + 1) % lines;
+        self.scrolled = true;
+
+        if self.hardware_scroll() {
+            self.dirty |= 1 << line;
+        } else {
+            self.dirty = u32::MAX;
+        }
+    }
+
+    /// Put a printable character at the cursor and advance it, wrapping to the next line
+    fn put_char(&mut self, c: u8) {
+        let (columns, lines) = self.get_size();
+
+        // Wrapping is deferred until the next character, so a newline right after the last
+        // column does not leave an empty line
+        if self.cursor.0 >= columns {
+            self.newline();
+        }
+
+        let line = (self.top + self.cursor.1) % lines;
+        self.cells[line as usize * columns as usize + self.cursor.0 as usize] = c;
+        self.dirty |= 1 << line;
+        self.cursor.0 += 1;
+    }
+}
+
+impl<DV, DI, const CS: usize, RST> fmt::Write for TerminalMode<DV, DI, CS, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match c {
+                '\n' => self.newline(),
+                '\r' => self.cursor.0 = 0,
+                ' '..='~' => self.put_char(c as u8),
+                _ => self.put_char(b'?'),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// 5x7 pixel glyphs for ASCII 0x20 to 0x7E. Each byte is a column with the top row in the least
+/// significant bit.
+const FONT_5X7: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5F, 0x00, 0x00], // '!'
+    [0x00, 0x07, 0x00, 0x07, 0x00], // '"'
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], // '#'
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], // '$'
+    [0x23, 0x13, 0x08, 0x64, 0x62], // '%'
+    [0x36, 0x49, 0x55, 0x22, 0x50], // '&'
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '''
+    [0x00, 0x1C, 0x22, 0x41, 0x00], // '('
+    [0x00, 0x41, 0x22, 0x1C, 0x00], // ')'
+    [0x08, 0x2A, 0x1C, 0x2A, 0x08], // '*'
+    [0x08, 0x08, 0x3E, 0x08, 0x08], // '+'
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ','
+    [0x08, 0x08, 0x08, 0x08, 0x08], // '-'
+    [0x00, 0x60, 0x60, 0x00, 0x00], // '.'
+    [0x20, 0x10, 0x08, 0x04, 0x02], // '/'
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], // '0'
+    [0x00, 0x42, 0x7F, 0x40, 0x00], // '1'
+    [0x42, 0x61, 0x51, 0x49, 0x46], // '2'
+    [0x21, 0x41, 0x45, 0x4B, 0x31], // '3'
+    [0x18, 0x14, 0x12, 0x7F, 0x10], // '4'
+    [0x27, 0x45, 0x45, 0x45, 0x39], // '5'
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], // '6'
+    [0x01, 0x71, 0x09, 0x05, 0x03], // '7'
+    [0x36, 0x49, 0x49, 0x49, 0x36], // '8'
+    [0x06, 0x49, 0x49, 0x29, 0x1E], // '9'
+    [0x00, 0x36, 0x36, 0x00, 0x00], // ':'
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ';'
+    [0x08, 0x14, 0x22, 0x41, 0x00], // '<'
+    [0x14, 0x14, 0x14, 0x14, 0x14], // '='
+    [0x00, 0x41, 0x22, 0x14, 0x08], // '>'
+    [0x02, 0x01, 0x51, 0x09, 0x06], // '?'
+    [0x32, 0x49, 0x79, 0x41, 0x3E], // '@'
+    [0x7E, 0x11, 0x11, 0x11, 0x7E], // 'A'
+    [0x7F, 0x49, 0x49, 0x49, 0x36], // 'B'
+    [0x3E, 0x41, 0x41, 0x41, 0x22], // 'C'
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], // 'D'
+    [0x7F, 0x49, 0x49, 0x49, 0x41], // 'E'
+    [0x7F, 0x09, 0x09, 0x09, 0x01], // 'F'
+    [0x3E, 0x41, 0x49, 0x49, 0x7A], // 'G'
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], // 'H'
+    [0x00, 0x41, 0x7F, 0x41, 0x00], // 'I'
+    [0x20, 0x40, 0x41, 0x3F, 0x01], // 'J'
+    [0x7F, 0x08, 0x14, 0x22, 0x41], // 'K'
+    [0x7F, 0x40, 0x40, 0x40, 0x40], // 'L'
+    [0x7F, 0x02, 0x0C, 0x02, 0x7F], // 'M'
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], // 'N'
+    [0x3E, 0x41, 0x41, 0x41, 0x3E], // 'O'
+    [0x7F, 0x09, 0x09, 0x09, 0x06], // 'P'
+    [0x3E, 0x41, 0x51, 0x21, 0x5E], // 'Q'
+    [0x7F, 0x09, 0x19, 0x29, 0x46], // 'R'
+    [0x46, 0x49, 0x49, 0x49, 0x31], // 'S'
+    [0x01, 0x01, 0x7F, 0x01, 0x01], // 'T'
+    [0x3F, 0x40, 0x40, 0x40, 0x3F], // 'U'
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], // 'V'
+    [0x3F, 0x40, 0x38, 0x40, 0x3F], // 'W'
+    [0x63, 0x14, 0x08, 0x14, 0x63], // 'X'
+    [0x07, 0x08, 0x70, 0x08, 0x07], // 'Y'
+    [0x61, 0x51, 0x49, 0x45, 0x43], // 'Z'
+    [0x00, 0x7F, 0x41, 0x41, 0x00], // '['
+    [0x02, 0x04, 0x08, 0x10, 0x20], // '\'
+    [0x00, 0x41, 0x41, 0x7F, 0x00], // ']'
+    [0x04, 0x02, 0x01, 0x02, 0x04], // '^'
+    [0x40, 0x40, 0x40, 0x40, 0x40], // '_'
+    [0x00, 0x01, 0x02, 0x04, 0x00], // '`'
+    [0x20, 0x54, 0x54, 0x54, 0x78], // 'a'
+    [0x7F, 0x48, 0x44, 0x44, 0x38], // 'b'
+    [0x38, 0x44, 0x44, 0x44, 0x20], // 'c'
+    [0x38, 0x44, 0x44, 0x48, 0x7F], // 'd'
+    [0x38, 0x54, 0x54, 0x54, 0x18], // 'e'
+    [0x08, 0x7E, 0x09, 0x01, 0x02], // 'f'
+    [0x0C, 0x52, 0x52, 0x52, 0x3E], // 'g'
+    [0x7F, 0x08, 0x04, 0x04, 0x78], // 'h'
+    [0x00, 0x44, 0x7D, 0x40, 0x00], // 'i'
+    [0x20, 0x40, 0x44, 0x3D, 0x00], // 'j'
+    [0x7F, 0x10, 0x28, 0x44, 0x00], // 'k'
+    [0x00, 0x41, 0x7F, 0x40, 0x00], // 'l'
+    [0x7C, 0x04, 0x18, 0x04, 0x78], // 'm'
+    [0x7C, 0x08, 0x04, 0x04, 0x78], // 'n'
+    [0x38, 0x44, 0x44, 0x44, 0x38], // 'o'
+    [0x7C, 0x14, 0x14, 0x14, 0x08], // 'p'
+    [0x08, 0x14, 0x14, 0x18, 0x7C], // 'q'
+    [0x7C, 0x08, 0x04, 0x04, 0x08], // 'r'
+    [0x48, 0x54, 0x54, 0x54, 0x20], // 's'
+    [0x04, 0x3F, 0x44, 0x40, 0x20], // 't'
+    [0x3C, 0x40, 0x40, 0x20, 0x7C], // 'u'
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], // 'v'
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], // 'w'
+    [0x44, 0x28, 0x10, 0x28, 0x44], // 'x'
+    [0x0C, 0x50, 0x50, 0x50, 0x3C], // 'y'
+    [0x44, 0x64, 0x54, 0x4C, 0x44], // 'z'
+    [0x00, 0x08, 0x36, 0x41, 0x00], // '{'
+    [0x00, 0x00, 0x7F, 0x00, 0x00], // '|'
+    [0x00, 0x41, 0x36, 0x08, 0x00], // '}'
+    [0x08, 0x04, 0x08, 0x10, 0x08], // '~'
+];
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::fmt::Write;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{
+        config::DisplayConfig,
+        displays::{sh1106::Sh1106_128_64, ssd1306::Ssd1306_128_32},
+        test_helpers::{block_on, command, MockInterface, Transfer},
+    };
+
+    fn terminal<DV: display::DisplayVariant>(
+        variant: DV,
+        iface: &MockInterface,
+    ) -> TerminalMode<DV, MockInterface> {
+        TerminalMode::new(DisplayProperties::new(
+            variant,
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        ))
+    }
+
+    /// Data sent for each redrawn line
+    fn lines(transfers: Vec<Transfer>) -> Vec<Vec<u8>> {
+        transfers
+            .into_iter()
+            .filter_map(|transfer| match transfer {
+                Transfer::Data(data) => Some(data),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Draw area commands and data of each redrawn page, in page order
+    fn pages(transfers: Vec<Transfer>) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut pages: Vec<_> = transfers
+            .windows(2)
+            .filter_map(|pair| match pair {
+                [Transfer::Commands(area), Transfer::Data(data)] => {
+                    Some((area.clone(), data.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        pages.sort();
+        pages
+    }
+
+    /// Page data of a line of text on a display `width` pixels wide
+    fn rendered(text: &str, width: usize) -> Vec<u8> {
+        let mut data = std::vec![0; width];
+        for (c, glyph) in text.bytes().zip(data.chunks_mut(CHAR_WIDTH)) {
+            glyph[..5].copy_from_slice(&FONT_5X7[(c - b' ') as usize]);
+        }
+        data
+    }
+
+    #[test]
+    fn newline_and_wrap() {
+        let mut term = terminal(Sh1106_128_64 {}, &MockInterface::new());
+        assert_eq!(term.get_size(), (21, 8));
+
+        term.write_str("ab\ncd").unwrap();
+        assert_eq!(term.get_cursor(), (2, 1));
+
+        term.write_str("\r").unwrap();
+        assert_eq!(term.get_cursor(), (0, 1));
+
+        // A full line leaves the cursor after the last column until the next character
+        term.write_str("abcdefghijklmnopqrstu").unwrap();
+        assert_eq!(term.get_cursor(), (21, 1));
+        term.write_str("v").unwrap();
+        assert_eq!(term.get_cursor(), (1, 2));
+
+        // A newline right after the last column moves down a single line
+        term.set_cursor(0, 3);
+        term.write_str("abcdefghijklmnopqrstu\n").unwrap();
+        assert_eq!(term.get_cursor(), (0, 4));
+    }
+
+    #[test]
+    fn cursor_clamping() {
+        let mut term = terminal(Sh1106_128_64 {}, &MockInterface::new());
+
+        term.set_cursor(5, 3);
+        assert_eq!(term.get_cursor(), (5, 3));
+        term.set_cursor(200, 200);
+        assert_eq!(term.get_cursor(), (20, 7));
+    }
+
+    #[test]
+    fn fmt_write() {
+        let iface = MockInterface::new();
+        let mut term = terminal(Sh1106_128_64 {}, &iface);
+        block_on(term.flush()).unwrap();
+        iface.take();
+
+        write!(term, "{}:{}\n\u{e9}", 4, 2).unwrap();
+        block_on(term.flush()).unwrap();
+
+        // Only the changed lines are sent, with unprintable characters replaced by '?'
+        assert_eq!(
+            lines(iface.take()),
+            [rendered("4:2", 128), rendered("?", 128)]
+        );
+    }
+
+    #[test]
+    fn hardware_scroll() {
+        let iface = MockInterface::new();
+        let mut term = terminal(Sh1106_128_64 {}, &iface);
+
+        write!(term, "0\n1\n2\n3\n4\n5\n6\n7").unwrap();
+        block_on(term.flush()).unwrap();
+        iface.take();
+
+        // The display start line moves down a page and only the new bottom line is drawn
+        write!(term, "\n8").unwrap();
+        block_on(term.flush()).unwrap();
+
+        let transfers = iface.take();
+        assert_eq!(transfers[0], command(&[0x48]));
+        assert_eq!(lines(transfers), [rendered("8", 128)]);
+        assert_eq!(term.get_cursor(), (1, 7));
+    }
+
+    #[test]
+    fn redraw_scroll() {
+        let iface = MockInterface::new();
+        let mut term = terminal(Ssd1306_128_32 {}, &iface);
+
+        // The 32 row panel uses half of the display RAM, so scrolling redraws every line
+        write!(term, "0\n1\n2\n3").unwrap();
+        block_on(term.flush()).unwrap();
+        iface.take();
+        write!(term, "\n4").unwrap();
+        block_on(term.flush()).unwrap();
+        let scrolled = iface.take();
+
+        // The same as drawing the remaining lines from scratch
+        let fresh = MockInterface::new();
+        let mut term = terminal(Ssd1306_128_32 {}, &fresh);
+        write!(term, "1\n2\n3\n4").unwrap();
+        block_on(term.flush()).unwrap();
+
+        assert!(!scrolled.contains(&command(&[0x40])));
+        let scrolled = pages(scrolled);
+        assert_eq!(scrolled.len(), 4);
+        assert_eq!(scrolled, pages(fresh.take()));
+    }
+}
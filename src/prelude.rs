@@ -1,3 +1,7 @@
 //! Crate prelude
 
-pub use super::{displayrotation::DisplayRotation, mode::GraphicsMode};
+pub use super::{
+    display::DisplayVariant,
+    displayrotation::DisplayRotation,
+    mode::{DoubleBufferedMode, GraphicsMode, GrayscaleMode},
+};
@@ -1,18 +1,43 @@
 //! Container to store and set display properties
 
-use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat};
+use embedded_hal_async::delay::DelayNs;
+use hal::digital::OutputPin;
 
-use crate::{command::Command, display::DisplayVariant, displayrotation::DisplayRotation};
+use core::ops::RangeInclusive;
+
+use crate::{
+    builder::NoOutputPin,
+    command::{Command, CommandBatch, NFrames, ScrollDirection},
+    config::DisplayConfig,
+    display::{
+        DisplayVariant, GrayScaleTable, GrayscaleVariant, HardwareScroll, OneColumnScroll,
+        PixelFormat,
+    },
+    displayrotation::DisplayRotation,
+    interface::WriteCommandsAndData,
+    Error,
+};
+
+/// Time for VCC to settle after the charge pump is turned on, and for the panel to discharge
+/// after the display is turned off, before the next power supply is switched
+const VCC_SETTLE_MS: u32 = 100;
+/// Time for VDD to settle before VCC is turned on
+const VDD_SETTLE_MS: u32 = 1;
 
 /// Display properties struct
-pub struct DisplayProperties<DV, DI> {
-    _variant: DV,
+pub struct DisplayProperties<DV, DI, RST = NoOutputPin> {
+    variant: DV,
     iface: DI,
     display_rotation: DisplayRotation,
+    config: DisplayConfig,
+    reset_pin: RST,
     draw_area_start: (u8, u8),
     draw_area_end: (u8, u8),
     draw_column: u8,
     draw_row: u8,
+    /// A page addressed draw moved on to the next row without sending its address yet
+    address_pending: bool,
 }
 
 impl<DV, DI> DisplayProperties<DV, DI>
@@ -20,28 +45,61 @@ where
     DI: AsyncWriteOnlyDataCommand,
     DV: DisplayVariant,
 {
-    /// Create new DisplayProperties instance
+    /// Create new DisplayProperties instance without a reset pin
     pub fn new(
         variant: DV,
         iface: DI,
         display_rotation: DisplayRotation,
+        config: DisplayConfig,
     ) -> DisplayProperties<DV, DI> {
         DisplayProperties {
-            _variant: variant,
+            variant,
             iface,
             display_rotation,
+            config,
+            reset_pin: NoOutputPin,
             draw_area_start: (0, 0),
             draw_area_end: (0, 0),
             draw_column: 0,
             draw_row: 0,
+            address_pending: false,
+        }
+    }
+}
+
+impl<DV, DI, RST> DisplayProperties<DV, DI, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: DisplayVariant,
+{
+    /// Use `reset_pin` to reset the display, replacing any previous reset pin
+    pub fn with_reset_pin<R: OutputPin>(self, reset_pin: R) -> DisplayProperties<DV, DI, R> {
+        DisplayProperties {
+            variant: self.variant,
+            iface: self.iface,
+            display_rotation: self.display_rotation,
+            config: self.config,
+            reset_pin,
+            draw_area_start: self.draw_area_start,
+            draw_area_end: self.draw_area_end,
+            draw_column: self.draw_column,
+            draw_row: self.draw_row,
+            address_pending: self.address_pending,
         }
     }
 
     /// Initialise the display in column mode (i.e. a byte walks down a column of 8 pixels) with
     /// column 0 on the left and column _(display_width - 1)_ on the right.
-    pub async fn init_column_mode(&mut self) -> Result<(), DisplayError> {
+    ///
+    /// Returns [`Error::InvalidConfig`] without sending anything if the
+    /// [`DisplayConfig`] is out of range.
+    pub async fn init_column_mode<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.config.validate()?;
+
         let display_rotation = self.display_rotation;
-        DV::init_column_mode(&mut self.iface).await?;
+        self.variant
+            .init(&mut self.iface, delay, &self.config)
+            .await?;
         self.set_rotation(display_rotation).await?;
 
         Ok(())
@@ -50,29 +108,83 @@ where
     /// Set the position in the framebuffer of the display where any sent data should be
     /// drawn. This method can be used for changing the affected area on the screen as well
     /// as (re-)setting the start point of the next `draw` call.
-    pub async fn set_draw_area(
-        &mut self,
-        start: (u8, u8),
-        end: (u8, u8),
-    ) -> Result<(), DisplayError> {
+    ///
+    /// `start` and `end` are given as (column, page) in display RAM with `end` being exclusive,
+    /// so columns start at the variant's column offset. Returns [`Error::InvalidDrawArea`] if
+    /// the area is empty or not within the display. Grayscale variants have no pages and return
+    /// [`Error::Unsupported`].
+    pub async fn set_draw_area(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), Error> {
+        self.check_draw_area(start, end)?;
+
         self.draw_area_start = start;
         self.draw_area_end = end;
         self.draw_column = start.0;
         self.draw_row = start.1;
+        self.address_pending = false;
 
-        self.send_draw_address().await
+        self.draw_address()?.send(&mut self.iface).await
+    }
+
+    fn check_draw_area(&self, start: (u8, u8), end: (u8, u8)) -> Result<(), Error> {
+        if !matches!(DV::PIXEL_FORMAT, PixelFormat::Mono) {
+            return Err(Error::Unsupported);
+        }
+
+        let (width, height) = self.variant.size();
+        let column_offset = u16::from(self.column_offset());
+        if start.0 >= end.0
+            || start.1 >= end.1
+            || u16::from(start.0) < column_offset
+            || u16::from(end.0) > column_offset + width
+            || u16::from(end.1) > height.div_ceil(8)
+        {
+            return Err(Error::InvalidDrawArea);
+        }
+
+        Ok(())
+    }
+
+    fn check_scroll_pages(&self, pages: &RangeInclusive<u8>) -> Result<(), Error> {
+        let (_, height) = self.variant.size();
+        if pages.is_empty() || u16::from(*pages.end()) >= height.div_ceil(8) {
+            return Err(Error::InvalidDrawArea);
+        }
+
+        Ok(())
     }
 
     /// Send the data to the display for drawing at the current position in the framebuffer
     /// and advance the position accordingly. Cf. `set_draw_area` to modify the affected area by
     /// this method.
-    pub async fn draw(&mut self, mut buffer: &[u8]) -> Result<(), DisplayError> {
+    ///
+    /// `buffer` does not need to fill whole rows of the draw area: a partial row is continued by
+    /// the next call, and data past the end of the area wraps around to its start. Returns
+    /// [`Error::InvalidDrawArea`] if no draw area has been set.
+    pub async fn draw(&mut self, mut buffer: &[u8]) -> Result<(), Error> {
+        if self.draw_area_start.0 >= self.draw_area_end.0 {
+            return Err(Error::InvalidDrawArea);
+        }
+
+        if self.variant.horizontal_addressing() {
+            // The controller wraps around the column and page range by itself
+            self.iface.send_data(DataFormat::U8(buffer)).await?;
+
+            return Ok(());
+        }
+
         while !buffer.is_empty() {
-            let count = self.draw_area_end.0 - self.draw_column;
+            // The next row is only addressed once there is data for it, so a draw filling the
+            // area exactly ends without a trailing address
+            if self.address_pending {
+                self.draw_address()?.send(&mut self.iface).await?;
+                self.address_pending = false;
+            }
+
+            let count = usize::from(self.draw_area_end.0 - self.draw_column).min(buffer.len());
             self.iface
-                .send_data(DataFormat::U8(&buffer[..count as usize]))
+                .send_data(DataFormat::U8(&buffer[..count]))
                 .await?;
-            self.draw_column += count;
+            self.draw_column += count as u8;
 
             if self.draw_column >= self.draw_area_end.0 {
                 self.draw_column = self.draw_area_start.0;
@@ -82,31 +194,54 @@ where
                     self.draw_row = self.draw_area_start.1;
                 }
 
-                self.send_draw_address().await?;
+                self.address_pending = true;
             }
 
-            buffer = &buffer[count as usize..];
+            buffer = &buffer[count..];
         }
 
         Ok(())
     }
 
-    async fn send_draw_address(&mut self) -> Result<(), DisplayError> {
-        if DV::LARGE_PAGE_ADDRESS {
-            Command::LargePageAddress(self.draw_row.into())
-                .send(&mut self.iface)
-                .await?;
+    /// Commands selecting the draw area as a column and page range, or the current position
+    /// for variants with page addressing
+    fn draw_address(&self) -> Result<CommandBatch<6>, Error> {
+        let mut batch = CommandBatch::new();
+
+        if self.variant.horizontal_addressing() {
+            batch
+                .push(Command::ColumnRange(
+                    self.draw_area_start.0,
+                    self.draw_area_end.0 - 1,
+                ))?
+                .push(Command::PageRange(
+                    self.draw_area_start.1,
+                    self.draw_area_end.1 - 1,
+                ))?;
         } else {
-            Command::PageAddress(self.draw_row.into())
-                .send(&mut self.iface)
-                .await?;
+            let page = if self.variant.large_page_address() {
+                Command::LargePageAddress(self.draw_row)
+            } else {
+                Command::PageAddress(self.draw_row)
+            };
+
+            batch
+                .push(page)?
+                .push(Command::ColumnAddressLow(0xF & self.draw_column))?
+                .push(Command::ColumnAddressHigh(0xF & (self.draw_column >> 4)))?;
         }
-        Command::ColumnAddressLow(0xF & self.draw_column)
-            .send(&mut self.iface)
-            .await?;
-        Command::ColumnAddressHigh(0xF & (self.draw_column >> 4))
-            .send(&mut self.iface)
-            .await
+
+        Ok(batch)
+    }
+
+    /// Get the display variant
+    pub fn get_variant(&self) -> &DV {
+        &self.variant
+    }
+
+    /// Get the settings applied by `init_column_mode`
+    pub fn get_config(&self) -> &DisplayConfig {
+        &self.config
     }
 
     // Get the configured display size
@@ -115,8 +250,8 @@ where
     //}
 
     /// Get display dimensions, taking into account the current rotation of the display
-    pub fn get_dimensions(&self) -> (u8, u8) {
-        let (w, h) = DV::dimensions();
+    pub fn get_dimensions(&self) -> (u16, u16) {
+        let (w, h) = self.variant.size();
 
         match self.display_rotation {
             DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (w, h),
@@ -129,41 +264,806 @@ where
         self.display_rotation
     }
 
+    /// Column offset of the display in the current rotation. The default
+    /// [`set_rotation`](DisplayVariant::set_rotation) remaps the segment order in `Rotate0` and
+    /// `Rotate270` only.
+    pub fn column_offset(&self) -> u8 {
+        match self.display_rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate270 => self.variant.column_offset(),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate180 => {
+                self.variant.unremapped_column_offset()
+            }
+        }
+    }
+
     /// Set the display rotation
-    pub async fn set_rotation(
-        &mut self,
-        display_rotation: DisplayRotation,
-    ) -> Result<(), DisplayError> {
+    pub async fn set_rotation(&mut self, display_rotation: DisplayRotation) -> Result<(), Error> {
         self.display_rotation = display_rotation;
 
-        match display_rotation {
-            DisplayRotation::Rotate0 => {
-                Command::SegmentRemap(true).send(&mut self.iface).await?;
-                Command::ReverseComDir(true).send(&mut self.iface).await
-            }
-            DisplayRotation::Rotate90 => {
-                Command::SegmentRemap(false).send(&mut self.iface).await?;
-                Command::ReverseComDir(true).send(&mut self.iface).await
-            }
-            DisplayRotation::Rotate180 => {
-                Command::SegmentRemap(false).send(&mut self.iface).await?;
-                Command::ReverseComDir(false).send(&mut self.iface).await
-            }
-            DisplayRotation::Rotate270 => {
-                Command::SegmentRemap(true).send(&mut self.iface).await?;
-                Command::ReverseComDir(false).send(&mut self.iface).await
-            }
-        }
+        self.variant
+            .rotation(&mut self.iface, display_rotation)
+            .await
     }
 
     /// Turn the display on or off. The display can be drawn to and retains all
     /// of its memory even while off.
-    pub async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+    pub async fn display_on(&mut self, on: bool) -> Result<(), Error> {
         Command::DisplayOn(on).send(&mut self.iface).await
     }
 
+    /// Turn the display and its charge pump off. The display RAM keeps its content.
+    pub async fn sleep(&mut self) -> Result<(), Error> {
+        Command::DisplayOn(false).send(&mut self.iface).await?;
+        self.variant.charge_pump(&mut self.iface, false).await
+    }
+
+    /// Turn the charge pump back on, unless VCC is supplied externally, and the display on once
+    /// VCC has settled
+    pub async fn wake<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        if !self.config.external_vcc {
+            self.variant.charge_pump(&mut self.iface, true).await?;
+            delay.delay_ms(VCC_SETTLE_MS).await;
+        }
+        Command::DisplayOn(true).send(&mut self.iface).await
+    }
+
+    /// Shut the display down in the order given by the datasheets: display and charge pump off,
+    /// then the VCC enable pin low and finally the VDD enable pin low, waiting for the panel to
+    /// discharge in between. The pins are passed in the same order as to `power_up`. Pass
+    /// [`NoOutputPin`] for a supply that is not switched.
+    pub async fn power_down<VDD, VCC, D>(
+        &mut self,
+        vdd: &mut VDD,
+        vcc: &mut VCC,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        VDD: OutputPin,
+        VCC: OutputPin,
+        D: DelayNs,
+    {
+        self.sleep().await?;
+        delay.delay_ms(VCC_SETTLE_MS).await;
+        vcc.set_low().map_err(Error::pin)?;
+        delay.delay_ms(VCC_SETTLE_MS).await;
+        vdd.set_low().map_err(Error::pin)
+    }
+
+    /// Switch the supplies back on after `power_down`: VDD enable pin high, then VCC enable pin
+    /// high once VDD has settled. The display RAM and settings are lost, so the display must be
+    /// initialised again afterwards.
+    pub async fn power_up<VDD, VCC, D>(
+        &mut self,
+        vdd: &mut VDD,
+        vcc: &mut VCC,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        VDD: OutputPin,
+        VCC: OutputPin,
+        D: DelayNs,
+    {
+        vdd.set_high().map_err(Error::pin)?;
+        delay.delay_ms(VDD_SETTLE_MS).await;
+        vcc.set_high().map_err(Error::pin)?;
+        delay.delay_ms(VCC_SETTLE_MS).await;
+
+        Ok(())
+    }
+
     /// Set the display contrast
-    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
-        Command::Contrast(contrast).send(&mut self.iface).await
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
+        self.variant.contrast(&mut self.iface, contrast).await
+    }
+
+    /// Set the display RAM row shown at the top of the display
+    pub async fn set_start_line(&mut self, line: u8) -> Result<(), Error> {
+        Command::StartLine(line).send(&mut self.iface).await
+    }
+
+    /// Send a single command to the display. The driver does not track its effect, so commands
+    /// changing the addressing or geometry can confuse the other methods.
+    pub async fn send_command(&mut self, command: Command) -> Result<(), Error> {
+        command.send(&mut self.iface).await
+    }
+}
+
+impl<DV, DI, RST> DisplayProperties<DV, DI, RST>
+where
+    DI: WriteCommandsAndData,
+    DV: DisplayVariant,
+{
+    /// Draw `data` into the area from `start` to `end` like `set_draw_area` followed by `draw`,
+    /// but send the address commands of each page in the same transfer as its data. With the
+    /// column and page range of SSD130x controllers the whole area takes a single transfer.
+    ///
+    /// `data` must fill the area exactly, or [`Error::InvalidDrawArea`] is returned. The draw
+    /// area is cleared afterwards, so a following `draw` needs a new `set_draw_area`.
+    pub async fn draw_combined(
+        &mut self,
+        start: (u8, u8),
+        end: (u8, u8),
+        data: &[u8],
+    ) -> Result<(), Error> {
+        self.check_draw_area(start, end)?;
+
+        let width = usize::from(end.0 - start.0);
+        if data.len() != width * usize::from(end.1 - start.1) {
+            return Err(Error::InvalidDrawArea);
+        }
+
+        self.draw_area_start = start;
+        self.draw_area_end = end;
+        self.draw_column = start.0;
+
+        if self.variant.horizontal_addressing() {
+            let address = self.draw_address()?;
+            self.iface
+                .send_commands_and_data(address.as_bytes(), data)
+                .await?;
+        } else {
+            for (page, data) in (start.1..end.1).zip(data.chunks(width)) {
+                self.draw_row = page;
+
+                let address = self.draw_address()?;
+                self.iface
+                    .send_commands_and_data(address.as_bytes(), data)
+                    .await?;
+            }
+        }
+
+        // The controller's position no longer matches the draw area
+        self.draw_area_end = self.draw_area_start;
+
+        Ok(())
+    }
+}
+
+impl<DV, DI, RST> DisplayProperties<DV, DI, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: DisplayVariant,
+    RST: OutputPin,
+{
+    /// Pulse the reset pin with the [`ResetTiming`](crate::display::ResetTiming) of the display variant. All settings and the
+    /// display RAM are lost, so the display must be initialised again afterwards.
+    pub async fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        let timing = self.variant.reset_timing();
+
+        self.reset_pin.set_high().map_err(Error::pin)?;
+        delay.delay_us(timing.recovery_us).await;
+        self.reset_pin.set_low().map_err(Error::pin)?;
+        delay.delay_us(timing.low_us).await;
+        self.reset_pin.set_high().map_err(Error::pin)?;
+        delay.delay_us(timing.recovery_us).await;
+
+        Ok(())
+    }
+}
+
+impl<DV, DI, RST> DisplayProperties<DV, DI, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: HardwareScroll,
+{
+    /// Start scrolling the given range of pages horizontally, moving one column every `interval`.
+    /// Any running scroll is stopped first. Returns [`Error::InvalidDrawArea`] if the range is
+    /// empty or not within the display.
+    pub async fn start_horizontal_scroll(
+        &mut self,
+        pages: RangeInclusive<u8>,
+        direction: ScrollDirection,
+        interval: NFrames,
+    ) -> Result<(), Error> {
+        self.check_scroll_pages(&pages)?;
+
+        // Scroll parameters must not be changed while scrolling is active
+        Command::EnableScroll(false).send(&mut self.iface).await?;
+        Command::HorizontalScroll(direction, *pages.start(), *pages.end(), interval)
+            .send(&mut self.iface)
+            .await?;
+        Command::EnableScroll(true).send(&mut self.iface).await
+    }
+
+    /// Start scrolling the given range of pages horizontally while the vertical scroll area moves
+    /// `vertical_offset` rows every `interval`. Any running scroll is stopped first. Returns
+    /// [`Error::InvalidDrawArea`] if the range is empty or not within the display.
+    pub async fn start_vertical_and_horizontal_scroll(
+        &mut self,
+        pages: RangeInclusive<u8>,
+        direction: ScrollDirection,
+        interval: NFrames,
+        vertical_offset: u8,
+    ) -> Result<(), Error> {
+        self.check_scroll_pages(&pages)?;
+
+        Command::EnableScroll(false).send(&mut self.iface).await?;
+        Command::VerticalAndHorizontalScroll(
+            direction,
+            *pages.start(),
+            *pages.end(),
+            interval,
+            vertical_offset,
+        )
+        .send(&mut self.iface)
+        .await?;
+        Command::EnableScroll(true).send(&mut self.iface).await
+    }
+
+    /// Set the rows moved by a vertical scroll. `fixed_rows` at the top stay in place and the
+    /// following `scroll_rows` rows scroll. Returns [`Error::InvalidDrawArea`] if the rows do not
+    /// fit on the display.
+    pub async fn set_vertical_scroll_area(
+        &mut self,
+        fixed_rows: u8,
+        scroll_rows: u8,
+    ) -> Result<(), Error> {
+        let (_, height) = self.variant.size();
+        if u16::from(fixed_rows) + u16::from(scroll_rows) > height {
+            return Err(Error::InvalidDrawArea);
+        }
+
+        Command::VerticalScrollArea(fixed_rows, scroll_rows)
+            .send(&mut self.iface)
+            .await
+    }
+
+    /// Stop any running scroll. The display RAM must be rewritten afterwards, as the controller
+    /// does not preserve it while scrolling.
+    pub async fn stop_scroll(&mut self) -> Result<(), Error> {
+        Command::EnableScroll(false).send(&mut self.iface).await
+    }
+}
+
+impl<DV, DI, RST> DisplayProperties<DV, DI, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: OneColumnScroll,
+{
+    /// Scroll the given range of pages by a single column. Scrolling must be stopped. Returns
+    /// [`Error::InvalidDrawArea`] if the range is empty or not within the display.
+    pub async fn scroll_one_column(
+        &mut self,
+        pages: RangeInclusive<u8>,
+        direction: ScrollDirection,
+    ) -> Result<(), Error> {
+        self.check_scroll_pages(&pages)?;
+
+        let (display_width, _) = self.variant.size();
+        let start_column = self.column_offset();
+        let end_column = start_column + (display_width - 1) as u8;
+
+        Command::OneColumnScroll(
+            direction,
+            *pages.start(),
+            *pages.end(),
+            start_column,
+            end_column,
+        )
+        .send(&mut self.iface)
+        .await
+    }
+}
+
+impl<DV, DI, RST> DisplayProperties<DV, DI, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+{
+    /// Send nibble packed rows of pixel data to the area from `start` to `end` (exclusive), both
+    /// given as (column, row) in pixels. The columns must be aligned to
+    /// [`COLUMN_UNIT`](GrayscaleVariant::COLUMN_UNIT) and `data` must cover the area exactly.
+    pub async fn draw_gray_area(
+        &mut self,
+        start: (u16, u16),
+        end: (u16, u16),
+        data: &[u8],
+    ) -> Result<(), Error> {
+        self.check_gray_area(start, end, data)?;
+
+        DV::draw_area(&mut self.iface, start, end, data).await
+    }
+
+    fn check_gray_area(
+        &self,
+        start: (u16, u16),
+        end: (u16, u16),
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let (width, height) = self.variant.size();
+        let column_offset = u16::from(DV::COLUMN_OFFSET);
+        if start.0 >= end.0
+            || start.1 >= end.1
+            || end.0 > width
+            || end.1 > height
+            || (column_offset + start.0) % DV::COLUMN_UNIT != 0
+            || (column_offset + end.0) % DV::COLUMN_UNIT != 0
+            || data.len() != usize::from(end.0 - start.0) / 2 * usize::from(end.1 - start.1)
+        {
+            return Err(Error::InvalidDrawArea);
+        }
+
+        Ok(())
+    }
+}
+
+impl<DV, DI, RST> DisplayProperties<DV, DI, RST>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: GrayScaleTable,
+{
+    /// Set the drive level of gray levels 1 to 15. `None` selects the built in linear table.
+    pub async fn set_gray_scale_table(&mut self, table: Option<&[u8; 15]>) -> Result<(), Error> {
+        DV::set_gray_scale_table(&mut self.iface, table).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{vec, vec::Vec};
+
+    use super::*;
+    use crate::{
+        displays::{
+            sh1106::Sh1106_128_64, sh1122::Sh1122_256_64, ssd1306::Ssd1306_128_32,
+            ssd1309::Ssd1309_128_64, ssd1322::Ssd1322_256_64, ssd1327::Ssd1327_128_128,
+        },
+        test_helpers::{block_on, command, commands, data, MockInterface, Transfer},
+        NoOutputPin,
+    };
+
+    #[test]
+    fn set_rotation() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Sh1106_128_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+
+        for (rotation, remap, com_dir) in [
+            (DisplayRotation::Rotate0, 0xA1, 0xC8),
+            (DisplayRotation::Rotate90, 0xA0, 0xC8),
+            (DisplayRotation::Rotate180, 0xA0, 0xC0),
+            (DisplayRotation::Rotate270, 0xA1, 0xC0),
+        ] {
+            block_on(properties.set_rotation(rotation)).unwrap();
+
+            assert_eq!(properties.get_rotation(), rotation);
+            assert_eq!(iface.take(), [commands([&[remap], &[com_dir]])]);
+        }
+    }
+
+    #[test]
+    fn draw_page_addressing() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Sh1106_128_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+
+        block_on(properties.set_draw_area((18, 1), (22, 3))).unwrap();
+        block_on(properties.draw(&[1, 2, 3, 4, 5, 6, 7, 8])).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                commands([&[0xB1], &[0x02], &[0x11]]),
+                data(&[1, 2, 3, 4]),
+                commands([&[0xB2], &[0x02], &[0x11]]),
+                data(&[5, 6, 7, 8]),
+            ]
+        );
+
+        // Drawing on wraps back to the start of the draw area
+        block_on(properties.draw(&[9])).unwrap();
+        assert_eq!(
+            iface.take(),
+            [commands([&[0xB1], &[0x02], &[0x11]]), data(&[9])]
+        );
+    }
+
+    #[test]
+    fn draw_partial_row() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Sh1106_128_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+
+        block_on(properties.set_draw_area((18, 1), (22, 3))).unwrap();
+        iface.take();
+        block_on(properties.draw(&[1, 2, 3, 4, 5, 6])).unwrap();
+        block_on(properties.draw(&[7, 8])).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                data(&[1, 2, 3, 4]),
+                commands([&[0xB2], &[0x02], &[0x11]]),
+                data(&[5, 6]),
+                // Continues the row where the last call stopped
+                data(&[7, 8]),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_draw_area() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Sh1106_128_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+
+        assert!(matches!(
+            block_on(properties.draw(&[1, 2, 3])),
+            Err(Error::InvalidDrawArea)
+        ));
+
+        for (start, end) in [
+            // Left of the column offset
+            ((1, 0), (10, 1)),
+            // Right of the last column
+            ((2, 0), (131, 1)),
+            // Below the last page
+            ((2, 7), (130, 9)),
+            // Empty
+            ((10, 0), (10, 1)),
+            ((10, 3), (20, 3)),
+        ] {
+            assert!(matches!(
+                block_on(properties.set_draw_area(start, end)),
+                Err(Error::InvalidDrawArea)
+            ));
+        }
+        assert_eq!(iface.take(), []);
+
+        block_on(properties.set_draw_area((2, 0), (130, 8))).unwrap();
+    }
+
+    #[test]
+    fn draw_combined() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Sh1106_128_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+
+        block_on(properties.draw_combined((18, 1), (20, 3), &[1, 2, 3, 4])).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                Transfer::CommandsAndData(vec![0xB1, 0x02, 0x11], vec![1, 2]),
+                Transfer::CommandsAndData(vec![0xB2, 0x02, 0x11], vec![3, 4]),
+            ]
+        );
+
+        assert!(matches!(
+            block_on(properties.draw(&[5])),
+            Err(Error::InvalidDrawArea)
+        ));
+        assert!(matches!(
+            block_on(properties.draw_combined((18, 1), (20, 3), &[1, 2, 3])),
+            Err(Error::InvalidDrawArea)
+        ));
+        assert_eq!(iface.take(), []);
+
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Ssd1306_128_32 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+
+        block_on(properties.draw_combined((8, 0), (10, 2), &[1, 2, 3, 4])).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [Transfer::CommandsAndData(
+                vec![0x21, 8, 9, 0x22, 0, 1],
+                vec![1, 2, 3, 4]
+            )]
+        );
+    }
+
+    #[test]
+    fn draw_area_grayscale_unsupported() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Sh1122_256_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+
+        assert!(matches!(
+            block_on(properties.set_draw_area((0, 0), (128, 2))),
+            Err(Error::Unsupported)
+        ));
+        assert_eq!(iface.take(), []);
+    }
+
+    #[test]
+    fn invalid_gray_area() {
+        let iface = MockInterface::new();
+        let mut sh1122 = DisplayProperties::new(
+            Sh1122_256_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+        let mut ssd1322 = DisplayProperties::new(
+            Ssd1322_256_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+        let mut ssd1327 = DisplayProperties::new(
+            Ssd1327_128_128 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+        let data = [0; 256];
+
+        for (start, end, len) in [
+            // Empty
+            ((8, 0), (8, 1), 0),
+            ((8, 0), (16, 0), 0),
+            // Right of the last column
+            ((252, 0), (260, 1), 4),
+            // Below the last row
+            ((0, 127), (8, 129), 8),
+            // Not aligned to a column address
+            ((1, 0), (9, 1), 4),
+            ((0, 0), (7, 1), 3),
+            // Data does not cover the area
+            ((0, 0), (8, 2), 4),
+            ((0, 0), (8, 2), 9),
+        ] {
+            assert!(matches!(
+                block_on(sh1122.draw_gray_area(start, end, &data[..len])),
+                Err(Error::InvalidDrawArea)
+            ));
+            assert!(matches!(
+                block_on(ssd1322.draw_gray_area(start, end, &data[..len])),
+                Err(Error::InvalidDrawArea)
+            ));
+            assert!(matches!(
+                block_on(ssd1327.draw_gray_area(start, end, &data[..len])),
+                Err(Error::InvalidDrawArea)
+            ));
+        }
+        // Aligned to pixel pairs, but not to the 4 pixels of an SSD1322 column address
+        assert!(matches!(
+            block_on(ssd1322.draw_gray_area((2, 0), (8, 1), &data[..3])),
+            Err(Error::InvalidDrawArea)
+        ));
+        assert_eq!(iface.take(), []);
+
+        block_on(sh1122.draw_gray_area((2, 0), (8, 1), &data[..3])).unwrap();
+        block_on(ssd1322.draw_gray_area((4, 0), (8, 1), &data[..2])).unwrap();
+        block_on(ssd1327.draw_gray_area((126, 127), (128, 128), &data[..1])).unwrap();
+    }
+
+    #[test]
+    fn init_invalid_config() {
+        let iface = MockInterface::new();
+        let config = DisplayConfig {
+            precharge: Some((0, 2)),
+            ..DisplayConfig::default()
+        };
+        let mut properties = DisplayProperties::new(
+            Sh1106_128_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            config,
+        );
+
+        assert!(matches!(
+            block_on(properties.init_column_mode(&mut iface.clone())),
+            Err(Error::InvalidConfig)
+        ));
+        assert_eq!(iface.take(), []);
+    }
+
+    #[test]
+    fn draw_horizontal_addressing() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Ssd1306_128_32 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+        let buffer: Vec<u8> = (0..=255).collect();
+
+        block_on(properties.set_draw_area((0, 0), (128, 2))).unwrap();
+        block_on(properties.draw(&buffer)).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [commands([&[0x21, 0, 127], &[0x22, 0, 1]]), data(&buffer)]
+        );
+    }
+
+    #[test]
+    fn sleep_and_wake() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Ssd1306_128_32 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+
+        block_on(properties.sleep()).unwrap();
+        block_on(properties.wake(&mut iface.clone())).unwrap();
+
+        assert_eq!(
+            iface.take(),
+            [
+                command(&[0xAE]),
+                command(&[0x8D, 0x10]),
+                command(&[0x8D, 0x14]),
+                Transfer::Delay(100_000_000),
+                command(&[0xAF]),
+            ]
+        );
+    }
+
+    #[test]
+    fn wake_external_vcc() {
+        let iface = MockInterface::new();
+        let config = DisplayConfig {
+            external_vcc: true,
+            ..DisplayConfig::default()
+        };
+        let mut properties = DisplayProperties::new(
+            Sh1106_128_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            config,
+        );
+
+        block_on(properties.wake(&mut iface.clone())).unwrap();
+
+        assert_eq!(iface.take(), [command(&[0xAF])]);
+    }
+
+    #[test]
+    fn power_down_and_up() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Sh1106_128_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+        let (mut vdd, mut vcc) = (iface.pin("vdd"), iface.pin("vcc"));
+
+        block_on(properties.power_down(&mut vdd, &mut vcc, &mut iface.clone())).unwrap();
+        assert_eq!(
+            iface.take(),
+            [
+                command(&[0xAE]),
+                command(&[0xAD, 0x8A]),
+                Transfer::Delay(100_000_000),
+                Transfer::Pin("vcc", false),
+                Transfer::Delay(100_000_000),
+                Transfer::Pin("vdd", false),
+            ]
+        );
+
+        block_on(properties.power_up(&mut vdd, &mut NoOutputPin, &mut iface.clone())).unwrap();
+        assert_eq!(
+            iface.take(),
+            [
+                Transfer::Pin("vdd", true),
+                Transfer::Delay(1_000_000),
+                Transfer::Delay(100_000_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn scroll_pages() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Ssd1306_128_32 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+        let (last, past_end) = (3, 4);
+
+        block_on(properties.start_horizontal_scroll(1..=last, ScrollDirection::Left, NFrames::F2))
+            .unwrap();
+        assert_eq!(
+            iface.take(),
+            [
+                command(&[0x2E]),
+                command(&[0x27, 0, 1, 0b111, 3, 0, 0xFF]),
+                command(&[0x2F]),
+            ]
+        );
+
+        for pages in [last..=1, 1..=past_end] {
+            assert!(matches!(
+                block_on(properties.start_horizontal_scroll(
+                    pages.clone(),
+                    ScrollDirection::Left,
+                    NFrames::F2,
+                )),
+                Err(Error::InvalidDrawArea)
+            ));
+            assert!(matches!(
+                block_on(properties.start_vertical_and_horizontal_scroll(
+                    pages,
+                    ScrollDirection::Left,
+                    NFrames::F2,
+                    1,
+                )),
+                Err(Error::InvalidDrawArea)
+            ));
+        }
+        assert_eq!(iface.take(), []);
+    }
+
+    #[test]
+    fn vertical_scroll_area() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Ssd1306_128_32 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+
+        block_on(properties.set_vertical_scroll_area(8, 24)).unwrap();
+        assert_eq!(iface.take(), [command(&[0xA3, 8, 24])]);
+
+        assert!(matches!(
+            block_on(properties.set_vertical_scroll_area(8, 25)),
+            Err(Error::InvalidDrawArea)
+        ));
+        assert_eq!(iface.take(), []);
+    }
+
+    #[test]
+    fn scroll_one_column_pages() {
+        let iface = MockInterface::new();
+        let mut properties = DisplayProperties::new(
+            Ssd1309_128_64 {},
+            iface.clone(),
+            DisplayRotation::Rotate0,
+            DisplayConfig::default(),
+        );
+        let (last, past_end) = (7, 8);
+
+        block_on(properties.scroll_one_column(0..=last, ScrollDirection::Right)).unwrap();
+        assert_eq!(iface.take(), [command(&[0x2C, 0, 0, 1, 7, 0, 127])]);
+
+        for pages in [last..=0, 0..=past_end] {
+            assert!(matches!(
+                block_on(properties.scroll_one_column(pages, ScrollDirection::Right)),
+                Err(Error::InvalidDrawArea)
+            ));
+        }
+        assert_eq!(iface.take(), []);
     }
 }
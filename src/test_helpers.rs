@@ -1,57 +1,181 @@
-//! Helpers for use in examples and tests
-
-use embedded_hal::{
-    blocking::{
-        i2c,
-        spi::{self, Transfer},
-    },
-    digital::v2::OutputPin,
+//! Helpers for use in tests
+
+extern crate std;
+
+use core::convert::Infallible;
+use std::{cell::RefCell, rc::Rc, vec::Vec};
+
+use display_interface::{
+    AsyncWriteOnlyDataCommand, DataFormat, DisplayError, WriteOnlyDataCommand,
 };
+use embedded_hal::digital::{ErrorType, OutputPin};
+use embedded_hal_async::delay::DelayNs;
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-pub struct SpiStub;
+pub use crate::blocking::block_on;
+use crate::{
+    config::DisplayConfig, display::DisplayVariant, displayrotation::DisplayRotation,
+    interface::WriteCommandsAndData, properties::DisplayProperties,
+};
 
-impl spi::Write<u8> for SpiStub {
-    type Error = ();
+/// A single call to the display interface
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transfer {
+    /// Bytes passed to `send_commands`
+    Commands(Vec<u8>),
+    /// Bytes passed to `send_data`
+    Data(Vec<u8>),
+    /// Nanoseconds passed to `delay_ns`
+    Delay(u32),
+    /// Level set on a named output pin
+    Pin(&'static str, bool),
+    /// Bytes passed to `send_commands_and_data`
+    CommandsAndData(Vec<u8>, Vec<u8>),
+}
 
-    fn write(&mut self, _buf: &[u8]) -> Result<(), ()> {
-        Ok(())
+/// Shorthand for an expected `send_commands` call
+pub fn command(bytes: &[u8]) -> Transfer {
+    Transfer::Commands(bytes.to_vec())
+}
+
+/// Shorthand for an expected `send_commands` call carrying a batch of commands
+pub fn commands<const N: usize>(batch: [&[u8]; N]) -> Transfer {
+    Transfer::Commands(batch.concat())
+}
+
+/// Shorthand for an expected `send_data` call
+pub fn data(bytes: &[u8]) -> Transfer {
+    Transfer::Data(bytes.to_vec())
+}
+
+/// Display interface that records every transfer, and delay that records every wait, in both
+/// their async and blocking flavours. Clones share the same log, so a clone can be kept to inspect what a driver that owns the interface has sent.
+#[derive(Debug, Clone, Default)]
+pub struct MockInterface {
+    transfers: Rc<RefCell<Vec<Transfer>>>,
+}
+
+impl MockInterface {
+    /// Create an interface with an empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an output pin that records its level changes in this log under `name`
+    pub fn pin(&self, name: &'static str) -> MockPin {
+        MockPin {
+            name,
+            transfers: self.transfers.clone(),
+        }
+    }
+
+    /// Remove and return all transfers recorded so far
+    pub fn take(&self) -> Vec<Transfer> {
+        self.transfers.take()
+    }
+
+    fn bytes(format: DataFormat<'_>) -> Result<Vec<u8>, DisplayError> {
+        match format {
+            DataFormat::U8(bytes) => Ok(bytes.to_vec()),
+            DataFormat::U8Iter(iter) => Ok(iter.collect()),
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
     }
 }
 
-impl Transfer<u8> for SpiStub {
-    type Error = ();
+impl AsyncWriteOnlyDataCommand for MockInterface {
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        let bytes = Self::bytes(cmd)?;
+        self.transfers.borrow_mut().push(Transfer::Commands(bytes));
+        Ok(())
+    }
 
-    fn transfer<'a>(&mut self, buf: &'a mut [u8]) -> Result<&'a [u8], ()> {
-        Ok(buf)
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        let bytes = Self::bytes(buf)?;
+        self.transfers.borrow_mut().push(Transfer::Data(bytes));
+        Ok(())
     }
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-pub struct PinStub;
+impl WriteCommandsAndData for MockInterface {
+    async fn send_commands_and_data(
+        &mut self,
+        commands: &[u8],
+        data: &[u8],
+    ) -> Result<(), DisplayError> {
+        self.transfers
+            .borrow_mut()
+            .push(Transfer::CommandsAndData(commands.to_vec(), data.to_vec()));
+        Ok(())
+    }
+}
 
-impl OutputPin for PinStub {
-    type Error = ();
+impl DelayNs for MockInterface {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.transfers.borrow_mut().push(Transfer::Delay(ns));
+    }
+}
 
-    fn set_high(&mut self) -> Result<(), ()> {
+impl WriteOnlyDataCommand for MockInterface {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        let bytes = Self::bytes(cmd)?;
+        self.transfers.borrow_mut().push(Transfer::Commands(bytes));
         Ok(())
     }
 
-    fn set_low(&mut self) -> Result<(), ()> {
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        let bytes = Self::bytes(buf)?;
+        self.transfers.borrow_mut().push(Transfer::Data(bytes));
         Ok(())
     }
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-pub struct I2cStub;
+impl hal::delay::DelayNs for MockInterface {
+    fn delay_ns(&mut self, ns: u32) {
+        self.transfers.borrow_mut().push(Transfer::Delay(ns));
+    }
+}
+
+/// Output pin that records every level change in the log of the interface it was created from
+#[derive(Debug)]
+pub struct MockPin {
+    name: &'static str,
+    transfers: Rc<RefCell<Vec<Transfer>>>,
+}
+
+impl ErrorType for MockPin {
+    type Error = Infallible;
+}
 
-impl i2c::Write for I2cStub {
-    type Error = ();
+impl OutputPin for MockPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.transfers
+            .borrow_mut()
+            .push(Transfer::Pin(self.name, false));
+        Ok(())
+    }
 
-    fn write(&mut self, _addr: u8, _buf: &[u8]) -> Result<(), ()> {
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.transfers
+            .borrow_mut()
+            .push(Transfer::Pin(self.name, true));
         Ok(())
     }
 }
+
+/// Initialise a display variant through `DisplayProperties` and return the transfers it sent
+pub fn init_transfers<DV: DisplayVariant>(variant: DV) -> Vec<Transfer> {
+    init_transfers_with(variant, DisplayConfig::default())
+}
+
+/// Initialise a display variant with the given settings and return the transfers it sent
+pub fn init_transfers_with<DV: DisplayVariant>(
+    variant: DV,
+    config: DisplayConfig,
+) -> Vec<Transfer> {
+    let iface = MockInterface::new();
+    let mut properties =
+        DisplayProperties::new(variant, iface.clone(), DisplayRotation::Rotate0, config);
+
+    block_on(properties.init_column_mode(&mut iface.clone())).unwrap();
+    iface.take()
+}